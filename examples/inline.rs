@@ -0,0 +1,24 @@
+use bararaq::components::Term;
+use bararaq::console::winsize::winsize;
+use bararaq::console::Session;
+use bararaq::frames;
+
+fn main() {
+    let mut reader = std::io::stdin().lock();
+    let mut writer = std::io::stdout().lock();
+    let _session = Session::new().raw_mode();
+
+    let ws = winsize::from_ioctl();
+    // a 3 lines tall viewport below the cursor, no alternate screen
+    let mut term = Term::inline(0, ws.cols(), 3);
+    term.open_inline(&mut reader, &mut writer);
+
+    for step in 0..5 {
+        // these lines stay in the scrollback above the viewport
+        term.insert_before(&mut writer, &[&format!("step {} done", step)]);
+        frames(2);
+    }
+
+    // the last frame stays on the screen after the program exits
+    term.close_inline(&mut writer);
+}
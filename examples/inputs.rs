@@ -1,46 +1,42 @@
-use bararaq::console::raw_mode::{cooked_mode, raw_mode};
-use bararaq::console::winsize::winsize;
+use bararaq::console::Session;
 use bararaq::inputs::keyboard::{Char, KbdEvent, Modifiers};
-use bararaq::inputs::mouse::{disable_mouse_input, enable_mouse_input};
-use bararaq::inputs::{event, read, Interaction};
+use bararaq::inputs::{poll_events, Decoder, Interaction};
 
 use std::io::Write;
 
 fn main() {
     // initialization
-    let ts = raw_mode();
-
     let mut writer = std::io::stdout().lock();
-    enable_mouse_input(&mut writer);
-    _ = writer.write(b"\x1b[?1049h\x1b[0;0f");
-    _ = writer.flush();
-
-    let mut ws = winsize::from_ioctl();
+    // the terminal gets restored when the session is dropped
+    let _session = Session::new()
+        .raw_mode()
+        .alternate_screen(&mut writer)
+        .mouse_input(&mut writer)
+        .bracketed_paste(&mut writer)
+        .focus_input(&mut writer)
+        .job_control();
 
     let mut reader = std::io::stdin().lock();
 
-    let mut i = vec![];
-
-    loop {
-        let input = read(&mut reader, &mut i);
-        print!("{:?}\r\n", input);
+    let mut decoder = Decoder::new();
 
-        let ui = event(input, &mut ws);
-        print!("{:?}\r\n{:?}\r\n\r\n", &ui.event, &ui.time);
-
-        // print!("{:?}\r\n", &ui);
-        _ = writer.flush();
-
-        if let Interaction::KbdEvent(KbdEvent {
-            char: Char::Char('c'),
-            modifiers: Modifiers(2),
-        }) = ui.event
-        {
+    'events: loop {
+        // the terminal hung up
+        let Ok(events) = poll_events(&mut reader, &mut decoder, None) else {
             break;
+        };
+        for ui in events {
+            print!("{:?}\r\n{:?}\r\n\r\n", &ui.event, &ui.time);
+            _ = writer.flush();
+
+            if let Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('c'),
+                modifiers: Modifiers(2),
+                ..
+            }) = ui.event
+            {
+                break 'events;
+            }
         }
     }
-
-    disable_mouse_input(&mut writer);
-    cooked_mode(ts);
-    _ = writer.write(b"\x1b[?1049l");
 }
@@ -57,7 +57,7 @@ fn main() {
 
     let (_, mut writer) = workers();
 
-    let ts = raw_mode();
+    let ts = raw_mode().unwrap();
     enter_alternate_screen(&mut writer);
 
     term.clear(&mut writer);
@@ -25,8 +25,8 @@ pub use builders::{BuilderError, BuilderTreeError};
 pub use builders::{ContainerBuilder, InputBuilder, NoEditBuilder, TermBuilder};
 pub use container::Container;
 pub(crate) use makers::*;
-pub use term::Term;
-pub use text::Text;
+pub use term::{Term, Viewport};
+pub use text::{Hyperlink, Text};
 
 use std::any::Any;
 
@@ -203,6 +203,23 @@ impl ComponentTree {
         Err(TreeError::BadID)
     }
 
+    /// sets the window title to the title of the focused Term
+    /// meant to be called whenever the focused Term changes
+    ///
+    /// # Errors
+    ///
+    /// returns an error if no Term in this tree is focused
+    pub fn render_title(&self, writer: &mut impl Write) -> Result<(), TreeError> {
+        match self.terms.values().find(|t| t.is_focused()) {
+            Some(term) => {
+                term.render_title(writer);
+
+                Ok(())
+            }
+            None => Err(TreeError::NoFocusedTerm),
+        }
+    }
+
     /// takes no id and automatically assigns an id while adding a new Term
     /// returns the new term id
     pub fn term_auto(&mut self) -> u8 {
@@ -8,6 +8,7 @@ pub struct TermBuilder {
     layout: Layout,
     area: Area,
     id: u8,
+    title: Option<String>,
 }
 
 impl TermBuilder {
@@ -16,6 +17,7 @@ impl TermBuilder {
             layout: Layout::Flex,
             area: Area::Zero,
             id: 0,
+            title: None,
         }
     }
 
@@ -33,6 +35,11 @@ impl TermBuilder {
         self
     }
 
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
     pub fn clear(self) -> Self {
         Self::new()
     }
@@ -45,6 +52,7 @@ impl TermBuilder {
                 id
             },
             layout: self.layout.clone(),
+            title: self.title.clone(),
             w: self.area.width().unwrap(),
             h: self.area.height().unwrap(),
             ..Term::default()
@@ -4,6 +4,7 @@ use std::io::Write;
 
 use crate::components::property::{Properties, Property};
 use crate::console::winsize::winsize;
+use crate::grapheme::Grapheme;
 use crate::render_pipeline;
 use crate::space::layout::Layout;
 use crate::space::{
@@ -17,9 +18,29 @@ use super::{ContainerBuilder, InputBuilder, NoEditBuilder};
 use super::{Container, Text};
 use super::{IdError, SpaceError, TreeError};
 
+/// where a Term gets drawn on the terminal window
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Viewport {
+    /// the Term takes up the whole window, normally inside the alternate screen
+    #[default]
+    Fullscreen,
+    /// the Term takes up the h lines below the cursor on the primary screen
+    /// and renders relative to the first of those lines
+    /// meant for prompts, progress bars and pickers
+    /// only the Term wide render, clear and render_cursor methods respect the viewport origin
+    Inline { h: u16 },
+}
+
 /// Term object that is basically the overall wrapper around back end for the terminal display
 #[derive(Debug, Default)]
 pub struct Term {
+    /// where this term gets drawn
+    pub viewport: Viewport,
+    /// the window row an inline term's viewport starts at, counting from 1
+    /// set by open_inline and moved down by insert_before
+    pub origin: u16,
+    /// the window title to show while this term is focused
+    pub title: Option<String>,
     /// this term's layout, applies only to direct children
     pub layout: Layout,
     /// this Term's id
@@ -72,6 +93,32 @@ impl Term {
         }
     }
 
+    /// returns a new inline term that is h lines tall
+    /// the lines get reserved below the cursor once open_inline is called
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bararaq::components::Term;
+    /// # use bararaq::console::winsize::winsize;
+    /// # let ws = winsize::from_ioctl();
+    /// # let mut reader = std::io::stdin().lock();
+    /// # let mut writer = std::io::stdout().lock();
+    /// let mut term = Term::inline(0, ws.cols(), 3);
+    /// term.open_inline(&mut reader, &mut writer);
+    /// term.insert_before(&mut writer, &["downloaded foo"]);
+    /// term.render(&mut writer);
+    /// term.close_inline(&mut writer);
+    /// ```
+    pub fn inline(id: u8, w: u16, h: u16) -> Self {
+        Term {
+            id,
+            w,
+            h,
+            viewport: Viewport::Inline { h },
+            ..Self::default()
+        }
+    }
+
     pub fn is_focused(&self) -> bool {
         self.attributes.contains("focused")
     }
@@ -499,7 +546,7 @@ impl Term {
         area: Area,
         border: Border,
         padding: Padding,
-        value: &[Option<char>],
+        value: &[Option<Grapheme>],
     ) -> Result<(), TreeError> {
         if !self.is_valid_noedit_id(&id) {
             eprintln!("bad id");
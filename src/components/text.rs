@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet};
 use std::io::StdoutLock;
 use std::io::Write;
+use std::ops::Range;
 
 use crate::colorscheme::ColorScheme;
 use crate::components::property::{Properties, Property};
 use crate::console::winsize::winsize;
+use crate::grapheme::Grapheme;
 use crate::render_pipeline;
 use crate::space::{area_conflicts, between, border::Border, border_fit, padding::Padding};
 
@@ -12,6 +14,21 @@ use super::Style;
 use super::{Container, Term};
 use super::{SpaceError, TreeError};
 
+/// a hyperlink over a range of a Text's value cells
+/// rendered through OSC 8 where the terminal supports it, as plain text otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperlink {
+    /// the value cells the link covers, as indices into the Text value
+    /// a range longer than the Text width wraps over the next lines
+    pub range: Range<usize>,
+    /// where the link leads, e.g., https://... or file://...
+    pub uri: String,
+    /// the OSC 8 link id
+    /// the terminal treats every cell with the same id as one link,
+    /// so a link that wraps over lines, or that gets redrawn in parts, still highlights as a whole
+    pub id: String,
+}
+
 /// Text objects are direct children of the Container objects
 /// and indirect children of the Term grand parent
 #[derive(Debug, Default)]
@@ -24,9 +41,11 @@ pub struct Text {
     pub id: [u8; 3],
     /// temporary value holder for use when scorrling history
     // this should be part of properties
-    pub temp: Vec<Option<char>>,
+    pub temp: Vec<Option<Grapheme>>,
     /// the value inside this Text object
-    pub value: Vec<Option<char>>,
+    pub value: Vec<Option<Grapheme>>,
+    /// the hyperlinks over parts of the value
+    pub hyperlinks: Vec<Hyperlink>,
     /// history cursor current value
     // this field should be part of properties
     pub hicu: usize,
@@ -76,7 +95,7 @@ impl Text {
         avpos: u16,
         w: u16,
         h: u16,
-        value: &[Option<char>],
+        value: &[Option<Grapheme>],
         border: Border,
         padding: Padding,
     ) -> Text {
@@ -102,6 +121,7 @@ impl Text {
 
                 v
             },
+            hyperlinks: vec![],
             crsh: 0,
             crsv: 0,
 
@@ -139,6 +159,30 @@ impl Text {
     //     }
     // }
 
+    /// makes the value cells in the given range a hyperlink to the uri
+    /// the link id is made out of this text's id and the link's index,
+    /// so it stays the same over redraws
+    pub fn hyperlink(&mut self, range: Range<usize>, uri: &str) {
+        let id = format!(
+            "bararaq-{}-{}-{}-{}",
+            self.id[0],
+            self.id[1],
+            self.id[2],
+            self.hyperlinks.len()
+        );
+
+        self.hyperlinks.push(Hyperlink {
+            range,
+            uri: uri.to_string(),
+            id,
+        });
+    }
+
+    /// returns the hyperlink the value cell at the given index is part of, if any
+    pub fn hyperlink_at(&self, idx: usize) -> Option<&Hyperlink> {
+        self.hyperlinks.iter().find(|l| l.range.contains(&idx))
+    }
+
     /// returns the id of the parent container of this text
     pub fn parent(&self) -> [u8; 2] {
         [self.id[0], self.id[1]]
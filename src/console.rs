@@ -1,24 +1,111 @@
+/// terminal capabilities detection; device attributes, DECRQM, env and terminfo
+pub mod capabilities;
+/// system clipboard access through OSC 52, with a built in base64 codec
+pub mod clipboard;
+/// bell, screen flash and desktop notifications through OSC 9 and OSC 777
+pub mod notify;
+/// pseudo terminal c ffi, a harness to run sessions, inputs and rendering without a real terminal
+pub mod pty;
 /// termios c ffi, raw mode utilities
 pub mod raw_mode;
+/// RAII guard that restores the terminal modes on drop and on panic
+pub mod session;
+/// signal c ffi, self-pipe that turns signals into pollable input
+pub mod signals;
+/// window title and icon name through OSC sequences, title stack push and pop
+pub mod title;
 /// winsize c ffi, use for getting the terminal window widtn and height
 pub mod winsize;
 
-use std::io::StdoutLock;
-use std::io::Write;
+use std::io::{BufRead, Write};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
 
-pub use raw_mode::{cooked_mode, raw_mode};
+use crate::inputs::read_timeout;
+
+pub use capabilities::{capabilities, Capabilities};
+pub use raw_mode::{cooked_mode, rare_mode, raw_mode, TermMode};
+pub use session::{resume, suspend, Session};
+pub use title::{pop_title, push_title, set_icon_name, set_title, set_title_and_icon};
 
 /// exits the terminal alternate screen back to the original screen
-pub fn leave_alternate_screen(writer: &mut StdoutLock) {
+pub fn leave_alternate_screen(writer: &mut impl Write) {
     _ = writer.write(b"\x1b[?1049l");
 }
 
 /// move to the terminal alternate screen from the defaut one
-pub fn enter_alternate_screen(writer: &mut StdoutLock) {
+pub fn enter_alternate_screen(writer: &mut impl Write) {
     _ = writer.write(b"\x1b[?1049h\x1b[0;0f");
     _ = writer.flush();
 }
 
+/// starts a synchronized update, DEC mode 2026
+/// the terminal holds off drawing until end_frame, so a frame never shows half drawn
+/// terminals without support ignore it
+/// Term::render already does this on its own when the capabilities probe found support;
+/// this is for programs that render components one by one
+pub fn begin_frame(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?2026h");
+}
+
+/// ends a synchronized update started with begin_frame and flushes the writer
+pub fn end_frame(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?2026l");
+    _ = writer.flush();
+}
+
+/// turns bracketed paste on
+/// pasted text then comes between \x1b[200~ and \x1b[201~,
+/// which the inputs Decoder turns into a single PasteEvent
+pub fn enable_bracketed_paste(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?2004h");
+}
+
+/// turns bracketed paste off
+/// pasted text then comes as if it was typed
+pub fn disable_bracketed_paste(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?2004l");
+}
+
+/// asks the terminal where the cursor is, CPR
+/// then reads the reply from the reader, usually stdin, until it arrives or the timeout runs out
+/// returns the cursor's [col, row], both starting at 1, or None if no reply arrived
+/// the terminal has to be in raw mode, otherwise the reply gets echoed and line buffered
+/// any input the user types before the reply arrives is lost
+pub fn cursor_position(
+    reader: &mut (impl BufRead + AsRawFd),
+    writer: &mut impl Write,
+    timeout: Duration,
+) -> Option<[u16; 2]> {
+    _ = writer.write(b"\x1b[6n");
+    _ = writer.flush();
+
+    let deadline = Instant::now() + timeout;
+    let mut replies = vec![];
+    let mut buffer = vec![];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match read_timeout(reader, &mut buffer, left) {
+            Ok(Some(bytes)) if !bytes.is_empty() => replies.extend_from_slice(bytes),
+            _ => return None,
+        }
+        if let Some(pos) = cursor_report(&replies) {
+            return Some(pos);
+        }
+    }
+}
+
+// the [col, row] of the first cursor position report in the bytes, \x1b[row;colR
+fn cursor_report(bytes: &[u8]) -> Option<[u16; 2]> {
+    bytes.split(|b| *b == 27).find_map(|seq| {
+        let body = seq.strip_prefix(b"[")?;
+        let end = body.iter().position(|b| *b == b'R')?;
+        let (row, col) = std::str::from_utf8(&body[..end]).ok()?.split_once(';')?;
+
+        Some([col.parse().ok()?, row.parse().ok()?])
+    })
+}
+
 pub fn workers() -> (std::io::StdinLock<'static>, std::io::StdoutLock<'static>) {
     (std::io::stdin().lock(), std::io::stdout().lock())
 }
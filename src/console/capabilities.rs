@@ -0,0 +1,547 @@
+use std::io::{BufRead, Write};
+use std::os::fd::AsRawFd;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::inputs::read_timeout;
+
+/// what the terminal is known to support
+/// made by the capabilities probe, or out of the environment alone through from_env
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Capabilities {
+    /// whether the terminal answered the probe at all
+    pub responded: bool,
+    /// the terminal name and version, as reported by XTVERSION
+    pub name: Option<String>,
+    /// the DA1 primary device attributes, e.g., 4 for sixel or 22 for ansi color
+    pub device_attributes: Vec<u16>,
+    /// the DA2 secondary device attributes; terminal type, firmware version and rom cartridge
+    pub secondary_attributes: Vec<u16>,
+    /// the value of the TERM env variable
+    pub term: Option<String>,
+    /// the number of colors the terminal supports, 0 if unknown
+    pub colors: u32,
+    /// whether 24 bits rgb colors are supported
+    pub truecolor: bool,
+    /// whether mouse reporting (?1003) is supported
+    pub mouse: bool,
+    /// whether the alternate screen (?1049) is supported
+    pub alternate_screen: bool,
+    /// whether bracketed paste (?2004) is supported
+    pub bracketed_paste: bool,
+    /// whether focus reporting (?1004) is supported
+    pub focus_events: bool,
+    /// whether synchronized output (?2026) is supported
+    pub synchronized_output: bool,
+    /// the desktop notifications protocol the terminal understands
+    pub notifications: Notifications,
+    /// whether OSC 8 hyperlinks are supported
+    pub hyperlinks: bool,
+}
+
+// the terminals known to support OSC 8 hyperlinks
+// like notifications, there is no query for them
+const HYPERLINKS: [&str; 10] = [
+    "iterm",
+    "kitty",
+    "wezterm",
+    "ghostty",
+    "foot",
+    "vte",
+    "alacritty",
+    "konsole",
+    "contour",
+    "rio",
+];
+
+fn hyperlinks(name: &str) -> bool {
+    let name = name.to_lowercase();
+    HYPERLINKS.iter().any(|t| name.contains(t))
+}
+
+/// the desktop notifications protocols
+/// there is no query for these, they are known from the terminal name
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Notifications {
+    /// no known protocol
+    #[default]
+    None,
+    /// OSC 9; body only, from iTerm2
+    Osc9,
+    /// OSC 777; title and body, from urxvt
+    Osc777,
+}
+
+impl Notifications {
+    // the protocol of the terminal with the given name or TERM value
+    fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if ["wezterm", "ghostty", "foot", "rxvt", "vte"]
+            .iter()
+            .any(|t| name.contains(t))
+        {
+            Self::Osc777
+        } else if ["iterm", "kitty"].iter().any(|t| name.contains(t)) {
+            Self::Osc9
+        } else {
+            Self::None
+        }
+    }
+}
+
+// the last probe result
+static DETECTED: Mutex<Option<Capabilities>> = Mutex::new(None);
+
+// the DEC private modes the probe asks about through DECRQM
+const MOUSE: u16 = 1003;
+const ALTERNATE_SCREEN: u16 = 1049;
+const BRACKETED_PASTE: u16 = 2004;
+const FOCUS_EVENTS: u16 = 1004;
+const SYNCHRONIZED_OUTPUT: u16 = 2026;
+
+/// probes the terminal for its capabilities
+/// sends the XTVERSION, DECRQM, DA2 and DA1 queries then reads the replies from the reader, usually stdin
+/// until the DA1 reply arrives or the timeout runs out;
+/// every terminal answers DA1, and answers in order, so DA1 marks the end of the replies
+/// the replies are then merged with the TERM and COLORTERM env variables and terminfo
+/// the result is kept, and can later be had from Capabilities::detected
+///
+/// the terminal has to be in raw mode, otherwise the replies get echoed and line buffered
+/// any input the user types while the probe runs is lost
+pub fn capabilities(
+    reader: &mut (impl BufRead + AsRawFd),
+    writer: &mut impl Write,
+    timeout: Duration,
+) -> Capabilities {
+    let mut q = String::from("\x1b[>0q");
+    [
+        MOUSE,
+        ALTERNATE_SCREEN,
+        BRACKETED_PASTE,
+        FOCUS_EVENTS,
+        SYNCHRONIZED_OUTPUT,
+    ]
+    .iter()
+    .for_each(|m| q.push_str(&format!("\x1b[?{}$p", m)));
+    q.push_str("\x1b[>c\x1b[c");
+    _ = writer.write(q.as_bytes());
+    _ = writer.flush();
+
+    let deadline = Instant::now() + timeout;
+    let mut replies = vec![];
+    let mut buffer = vec![];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match read_timeout(reader, &mut buffer, left) {
+            Ok(Some(bytes)) if !bytes.is_empty() => replies.extend_from_slice(bytes),
+            _ => break,
+        }
+        if has_da1(&replies) {
+            break;
+        }
+    }
+
+    let mut caps = Capabilities::from_env();
+    caps.merge_replies(&replies);
+
+    *DETECTED.lock().unwrap_or_else(|e| e.into_inner()) = Some(caps.clone());
+
+    caps
+}
+
+impl Capabilities {
+    /// the result of the last capabilities probe
+    /// falls back to from_env if the terminal was never probed
+    pub fn detected() -> Self {
+        DETECTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_else(Self::from_env)
+    }
+
+    /// whether the last probe result passes the given check
+    /// false if the terminal was never probed
+    /// cheaper than detected, since nothing gets cloned nor read from the env,
+    /// so it can be called on every frame
+    pub fn probed(check: impl FnOnce(&Self) -> bool) -> bool {
+        DETECTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(check)
+    }
+
+    /// guesses the capabilities from the TERM and COLORTERM env variables and terminfo alone,
+    /// without talking to the terminal
+    pub fn from_env() -> Self {
+        let term = std::env::var("TERM").ok();
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+
+        let mut caps = Self {
+            term: term.clone(),
+            ..Self::default()
+        };
+
+        // TERM_PROGRAM names the terminal better than TERM, which is often just xterm-256color
+        caps.notifications = match std::env::var("TERM_PROGRAM") {
+            Ok(program) => Notifications::from_name(&program),
+            Err(_) if std::env::var_os("VTE_VERSION").is_some() => Notifications::Osc777,
+            Err(_) => Notifications::from_name(term.as_deref().unwrap_or_default()),
+        };
+        caps.hyperlinks = std::env::var("TERM_PROGRAM").is_ok_and(|p| hyperlinks(&p))
+            || term.as_deref().is_some_and(hyperlinks)
+            || ["VTE_VERSION", "KONSOLE_VERSION", "WT_SESSION"]
+                .iter()
+                .any(|v| std::env::var_os(v).is_some());
+
+        let Some(term) = term else {
+            return caps;
+        };
+
+        if term == "dumb" {
+            return caps;
+        }
+
+        // the linux console has neither the alternate screen nor mouse reporting
+        let xterm_like = !term.starts_with("linux");
+        caps.mouse = xterm_like;
+        caps.alternate_screen = xterm_like;
+
+        if let Some(ti) = terminfo::load(&term) {
+            caps.colors = ti.colors;
+            caps.truecolor = ti.truecolor;
+        }
+        if caps.colors == 0 {
+            caps.colors = if term.contains("256color") {
+                256
+            } else if term.contains("color") || xterm_like {
+                8
+            } else {
+                0
+            };
+        }
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            caps.truecolor = true;
+        }
+        if caps.truecolor {
+            caps.colors = 1 << 24;
+        }
+
+        caps
+    }
+
+    // reads the terminal's replies into self
+    // what the terminal says overrides the env guesses
+    fn merge_replies(&mut self, replies: &[u8]) {
+        replies::parse(replies).into_iter().for_each(|r| match r {
+            replies::Reply::PrimaryAttributes(v) => {
+                self.responded = true;
+                self.device_attributes = v;
+            }
+            replies::Reply::SecondaryAttributes(v) => self.secondary_attributes = v,
+            replies::Reply::Version(name) => {
+                let notifications = Notifications::from_name(&name);
+                if notifications != Notifications::None {
+                    self.notifications = notifications;
+                }
+                self.hyperlinks |= hyperlinks(&name);
+                self.name = Some(name);
+            }
+            replies::Reply::Mode(mode, state) => {
+                // 0 not recognized, 1 set, 2 reset, 3 permanently set, 4 permanently reset
+                let supported = (1..=3).contains(&state);
+                match mode {
+                    MOUSE => self.mouse = supported,
+                    ALTERNATE_SCREEN => self.alternate_screen = supported,
+                    BRACKETED_PASTE => self.bracketed_paste = supported,
+                    FOCUS_EVENTS => self.focus_events = supported,
+                    SYNCHRONIZED_OUTPUT => self.synchronized_output = supported,
+                    _ => (),
+                }
+            }
+        });
+    }
+}
+
+fn has_da1(bytes: &[u8]) -> bool {
+    replies::parse(bytes)
+        .iter()
+        .any(|r| matches!(r, replies::Reply::PrimaryAttributes(_)))
+}
+
+// terminal query replies parsing
+mod replies {
+    #[derive(Debug, PartialEq)]
+    pub(super) enum Reply {
+        /// CSI ? Ps ; ... c
+        PrimaryAttributes(Vec<u16>),
+        /// CSI > Ps ; ... c
+        SecondaryAttributes(Vec<u16>),
+        /// DCS > | text ST
+        Version(String),
+        /// CSI ? Ps ; Pm $ y
+        Mode(u16, u8),
+    }
+
+    fn params(bytes: &[u8]) -> Vec<u16> {
+        bytes
+            .split(|b| *b == b';')
+            .map(|p| {
+                std::str::from_utf8(p)
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// finds every known reply in the bytes, ignoring anything else
+    pub(super) fn parse(bytes: &[u8]) -> Vec<Reply> {
+        let mut v = vec![];
+        let mut idx = 0;
+
+        while idx + 1 < bytes.len() {
+            if bytes[idx] != 27 {
+                idx += 1;
+                continue;
+            }
+
+            match bytes[idx + 1] {
+                // CSI; parameter and intermediate bytes then a final byte in 0x40..=0x7e
+                b'[' => {
+                    let start = idx + 2;
+                    let Some(len) = bytes[start..]
+                        .iter()
+                        .position(|b| (0x40..=0x7e).contains(b))
+                    else {
+                        break;
+                    };
+                    let end = start + len;
+                    let body = &bytes[start..end];
+
+                    match (body.first(), bytes[end]) {
+                        (Some(b'?'), b'c') => v.push(Reply::PrimaryAttributes(params(&body[1..]))),
+                        (Some(b'>'), b'c') => {
+                            v.push(Reply::SecondaryAttributes(params(&body[1..])))
+                        }
+                        (Some(b'?'), b'y') if body.ends_with(b"$") => {
+                            let p = params(&body[1..body.len() - 1]);
+                            if p.len() == 2 {
+                                v.push(Reply::Mode(p[0], p[1] as u8));
+                            }
+                        }
+                        _ => (),
+                    }
+
+                    idx = end + 1;
+                }
+                // DCS; ends with ST (ESC \)
+                b'P' => {
+                    let start = idx + 2;
+                    let Some(len) = bytes[start..].windows(2).position(|w| w == b"\x1b\\") else {
+                        break;
+                    };
+                    let end = start + len;
+                    let body = &bytes[start..end];
+
+                    if body.starts_with(b">|") {
+                        v.push(Reply::Version(
+                            String::from_utf8_lossy(&body[2..]).into_owned(),
+                        ));
+                    }
+
+                    idx = end + 2;
+                }
+                _ => idx += 1,
+            }
+        }
+
+        v
+    }
+}
+
+// compiled terminfo database reading
+// only reads the few capabilities that bararaq cares about
+mod terminfo {
+    use std::path::PathBuf;
+
+    pub(super) struct Terminfo {
+        pub(super) colors: u32,
+        pub(super) truecolor: bool,
+    }
+
+    // from term(5)
+    const MAGIC_LEGACY: u16 = 0o432;
+    const MAGIC_32BIT: u16 = 0o1036;
+    // the index of max_colors in the numbers section
+    const MAX_COLORS: usize = 13;
+
+    fn dirs() -> Vec<PathBuf> {
+        let mut v = vec![];
+        if let Ok(dir) = std::env::var("TERMINFO") {
+            v.push(PathBuf::from(dir));
+        }
+        if let Ok(home) = std::env::var("HOME") {
+            v.push(PathBuf::from(home).join(".terminfo"));
+        }
+        if let Ok(dirs) = std::env::var("TERMINFO_DIRS") {
+            v.extend(dirs.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+        }
+        v.extend(
+            [
+                "/etc/terminfo",
+                "/lib/terminfo",
+                "/usr/share/terminfo",
+                "/usr/lib/terminfo",
+            ]
+            .map(PathBuf::from),
+        );
+
+        v
+    }
+
+    /// finds and reads the terminfo entry of the given terminal name
+    pub(super) fn load(term: &str) -> Option<Terminfo> {
+        let first = term.chars().next()?;
+        dirs().into_iter().find_map(|dir| {
+            // linux uses the first letter for the subdirectory, macos its hex value
+            [first.to_string(), format!("{:x}", first as u32)]
+                .iter()
+                .find_map(|sub| std::fs::read(dir.join(sub).join(term)).ok())
+                .and_then(|bytes| parse(&bytes))
+        })
+    }
+
+    fn short(bytes: &[u8], idx: usize) -> Option<i16> {
+        Some(i16::from_le_bytes([*bytes.get(idx)?, *bytes.get(idx + 1)?]))
+    }
+
+    fn int(bytes: &[u8], idx: usize) -> Option<i32> {
+        Some(i32::from_le_bytes(
+            bytes.get(idx..idx + 4)?.try_into().ok()?,
+        ))
+    }
+
+    pub(super) fn parse(bytes: &[u8]) -> Option<Terminfo> {
+        let num_size = match short(bytes, 0)? as u16 {
+            MAGIC_LEGACY => 2,
+            MAGIC_32BIT => 4,
+            _ => return None,
+        };
+        let header = (1..6)
+            .map(|i| short(bytes, i * 2).map(|s| s.max(0) as usize))
+            .collect::<Option<Vec<usize>>>()?;
+        let [names, bools, nums, strs, table] =
+            [header[0], header[1], header[2], header[3], header[4]];
+
+        let mut idx = 12 + names + bools;
+        idx += idx % 2;
+
+        let number = |i: usize| -> Option<i32> {
+            match num_size {
+                2 => short(bytes, idx + i * 2).map(|n| n as i32),
+                _ => int(bytes, idx + i * 4),
+            }
+        };
+
+        let colors = if MAX_COLORS < nums {
+            number(MAX_COLORS).unwrap_or(-1).max(0) as u32
+        } else {
+            0
+        };
+
+        idx += nums * num_size + strs * 2 + table;
+        idx += idx % 2;
+
+        Some(Terminfo {
+            colors,
+            truecolor: extended_booleans(bytes, idx, num_size)
+                .iter()
+                .any(|name| name == "RGB" || name == "Tc"),
+        })
+    }
+
+    // returns the names of the extended booleans that are set
+    fn extended_booleans(bytes: &[u8], mut idx: usize, num_size: usize) -> Vec<String> {
+        let Some(header) = (0..5)
+            .map(|i| short(bytes, idx + i * 2).map(|s| s.max(0) as usize))
+            .collect::<Option<Vec<usize>>>()
+        else {
+            return vec![];
+        };
+        let [bools, nums, strs] = [header[0], header[1], header[2]];
+        idx += 10;
+
+        let flags = match bytes.get(idx..idx + bools) {
+            Some(f) => f.to_vec(),
+            None => return vec![],
+        };
+        idx += bools;
+        idx += idx % 2;
+        idx += nums * num_size;
+
+        let offsets = (0..strs + bools + nums + strs)
+            .map(|i| short(bytes, idx + i * 2).unwrap_or(-1))
+            .collect::<Vec<i16>>();
+        let table = &bytes[(idx + offsets.len() * 2).min(bytes.len())..];
+
+        let cstr = |start: usize| -> Option<&[u8]> {
+            let s = table.get(start..)?;
+            Some(&s[..s.iter().position(|b| *b == 0)?])
+        };
+
+        // the names come right after the extended string values in the table
+        let names_start = offsets[..strs]
+            .iter()
+            .filter(|o| **o >= 0)
+            .filter_map(|o| cstr(*o as usize).map(|s| *o as usize + s.len() + 1))
+            .max()
+            .unwrap_or(0);
+
+        offsets[strs..strs + bools]
+            .iter()
+            .zip(flags)
+            .filter(|(o, f)| **o >= 0 && *f == 1)
+            .filter_map(|(o, _)| cstr(names_start + *o as usize))
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod probe {
+    use super::replies::{parse, Reply};
+    use super::Capabilities;
+
+    #[test]
+    fn replies() {
+        let bytes = b"\x1bP>|kitty(0.35.2)\x1b\\\x1b[?2026;2$y\x1b[?1049;1$y\x1b[?2004;0$y\x1b[>1;4000;21c\x1b[?62;22;52c";
+        assert_eq!(
+            parse(bytes),
+            vec![
+                Reply::Version("kitty(0.35.2)".into()),
+                Reply::Mode(2026, 2),
+                Reply::Mode(1049, 1),
+                Reply::Mode(2004, 0),
+                Reply::SecondaryAttributes(vec![1, 4000, 21]),
+                Reply::PrimaryAttributes(vec![62, 22, 52]),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut caps = Capabilities {
+            bracketed_paste: true,
+            ..Capabilities::default()
+        };
+        // user input mixed in with the replies is skipped
+        caps.merge_replies(b"a\x1b[?2026;2$yb\x1b[?2004;4$y\x1b[?1;2c");
+
+        assert!(caps.responded);
+        assert!(caps.synchronized_output);
+        assert!(!caps.bracketed_paste);
+        assert_eq!(caps.device_attributes, vec![1, 2]);
+    }
+}
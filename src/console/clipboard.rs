@@ -0,0 +1,168 @@
+use std::io::Write;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// the selection buffers that OSC 52 can reach
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Selection {
+    /// the system clipboard, what ctrl-c/ctrl-v use
+    #[default]
+    Clipboard,
+    /// the primary selection, what middle click pastes on x11
+    Primary,
+}
+
+impl Selection {
+    fn code(&self) -> char {
+        match self {
+            Self::Clipboard => 'c',
+            Self::Primary => 'p',
+        }
+    }
+
+    fn from_code(code: &[u8]) -> Self {
+        match code {
+            b"p" => Self::Primary,
+            _ => Self::Clipboard,
+        }
+    }
+}
+
+/// the content of a selection, as replied by the terminal to a clipboard query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardEvent {
+    pub selection: Selection,
+    pub text: String,
+}
+
+/// puts the given text on the selection, OSC 52
+/// works over ssh too, since the terminal itself sets the clipboard
+/// some terminals turn OSC 52 off, or limit how long the text can be
+pub fn copy(writer: &mut impl Write, selection: Selection, text: &str) {
+    _ = writer.write(
+        format!(
+            "\x1b]52;{};{}\x07",
+            selection.code(),
+            encode(text.as_bytes())
+        )
+        .as_bytes(),
+    );
+}
+
+/// empties the selection
+pub fn clear(writer: &mut impl Write, selection: Selection) {
+    _ = writer.write(format!("\x1b]52;{};!\x07", selection.code()).as_bytes());
+}
+
+/// asks the terminal for the content of the selection
+/// the reply comes through the inputs Decoder as a ClipboardEvent
+/// most terminals only reply if the user allowed it, so the reply may never come
+pub fn query(writer: &mut impl Write, selection: Selection) {
+    _ = writer.write(format!("\x1b]52;{};?\x07", selection.code()).as_bytes());
+}
+
+/// decodes the body of an OSC 52 reply, i.e., what comes between '\x1b]52;' and the BEL or ST
+/// returns None if the body is not valid base64
+pub fn decode_reply(body: &[u8]) -> Option<ClipboardEvent> {
+    let sep = body.iter().position(|b| *b == b';')?;
+    let text = decode(&body[sep + 1..])?;
+
+    Some(ClipboardEvent {
+        selection: Selection::from_code(&body[..sep]),
+        text: String::from_utf8_lossy(&text).into_owned(),
+    })
+}
+
+/// encodes the bytes to padded standard base64
+pub fn encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - i * 8));
+
+        // 3 bytes make 4 chars, a shorter chunk gets padded
+        (0..4).for_each(|i| match i <= chunk.len() {
+            true => s.push(BASE64[(n >> (18 - i * 6)) as usize & 0x3f] as char),
+            false => s.push('='),
+        });
+    }
+
+    s
+}
+
+/// decodes standard base64, the padding is optional
+/// returns None on any other char, on a '=' that is not part of the trailing padding,
+/// or on input that no bytes encode to, e.g., a lone char
+pub fn decode(s: &[u8]) -> Option<Vec<u8>> {
+    // at most 2 '=' pad the end, any other '=' fails the lookup below
+    let s = s
+        .strip_suffix(b"==")
+        .or_else(|| s.strip_suffix(b"="))
+        .unwrap_or(s);
+    // every byte takes at least 2 chars
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut v = Vec::with_capacity(s.len() / 4 * 3);
+    let (mut n, mut bits) = (0u32, 0);
+    for c in s {
+        let sextet = BASE64.iter().position(|b| b == c)? as u32;
+        n = n << 6 | sextet;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            v.push((n >> bits) as u8);
+        }
+    }
+    // encode pads the last char with 0 bits
+    if n & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+
+    Some(v)
+}
+
+#[cfg(test)]
+mod base64 {
+    use super::{decode, decode_reply, encode, Selection};
+
+    #[test]
+    fn round_trip() {
+        [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("bararaq\n", "YmFyYXJhcQo="),
+        ]
+        .into_iter()
+        .for_each(|(text, b64)| {
+            assert_eq!(encode(text.as_bytes()), b64);
+            assert_eq!(decode(b64.as_bytes()).unwrap(), text.as_bytes());
+        });
+
+        let text = "نص ✓ 🦀";
+        assert_eq!(
+            decode(encode(text.as_bytes()).as_bytes()).unwrap(),
+            text.as_bytes()
+        );
+        assert_eq!(decode(b"Zm9v!"), None);
+    }
+
+    #[test]
+    fn reply() {
+        let event = decode_reply(b"p;Zm9vYg").unwrap();
+        assert_eq!(event.selection, Selection::Primary);
+        assert_eq!(event.text, "foob");
+
+        assert_eq!(decode_reply(b"c;Zm=9v"), None);
+        assert_eq!(decode_reply(b"c;Zg==="), None);
+        assert_eq!(decode_reply(b"c;=Zm9v"), None);
+        assert_eq!(decode_reply(b"c;Z"), None);
+        assert_eq!(decode_reply(b"c;Zm9vY"), None);
+        assert_eq!(decode_reply(b"c;Zh=="), None);
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::Write;
+use std::time::Duration;
+
+use super::capabilities::{Capabilities, Notifications};
+
+// control chars would end the OSC string early
+fn clean(s: &str) -> String {
+    s.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// rings the terminal bell
+/// depending on the terminal settings it beeps, flashes or marks the tab
+pub fn bell(writer: &mut impl Write) {
+    _ = writer.write(b"\x07");
+    _ = writer.flush();
+}
+
+/// flashes the screen by turning reverse video (DECSCNM) on for the given duration
+/// blocks for that duration
+/// terminals without DECSCNM show nothing
+pub fn flash(writer: &mut impl Write, duration: Duration) {
+    _ = writer.write(b"\x1b[?5h");
+    _ = writer.flush();
+    std::thread::sleep(duration);
+    _ = writer.write(b"\x1b[?5l");
+    _ = writer.flush();
+}
+
+/// sends a desktop notification
+/// uses OSC 777 or OSC 9, whichever the detected terminal understands;
+/// OSC 9 has no title, so the title is prepended to the body
+/// falls back to the bell when the terminal supports neither,
+/// since an unknown OSC may be drawn as garbage by some terminals
+pub fn notify(writer: &mut impl Write, title: &str, body: &str) {
+    notify_with(writer, Capabilities::detected().notifications, title, body);
+}
+
+/// sends a desktop notification with the given protocol, whatever the terminal supports
+pub fn notify_with(writer: &mut impl Write, protocol: Notifications, title: &str, body: &str) {
+    let (title, body) = (clean(title), clean(body));
+    match protocol {
+        // fields are separated by ';', only the body may hold one
+        Notifications::Osc777 => {
+            _ = writer.write(
+                format!("\x1b]777;notify;{};{}\x07", title.replace(';', ","), body).as_bytes(),
+            )
+        }
+        Notifications::Osc9 => {
+            let text = match title.is_empty() {
+                true => body,
+                false => format!("{}: {}", title, body),
+            };
+            _ = writer.write(format!("\x1b]9;{}\x07", text).as_bytes());
+        }
+        Notifications::None => _ = writer.write(b"\x07"),
+    }
+    _ = writer.flush();
+}
+
+#[cfg(test)]
+mod notify {
+    use super::{notify_with, Notifications};
+
+    #[test]
+    fn protocols() {
+        let mut v = vec![];
+        notify_with(&mut v, Notifications::Osc777, "build; done", "0 errors\x1b");
+        assert_eq!(v, b"\x1b]777;notify;build, done;0 errors\x07");
+
+        v.clear();
+        notify_with(&mut v, Notifications::Osc9, "build", "done");
+        assert_eq!(v, b"\x1b]9;build: done\x07");
+
+        v.clear();
+        notify_with(&mut v, Notifications::None, "build", "done");
+        assert_eq!(v, b"\x07");
+    }
+}
@@ -0,0 +1,341 @@
+use std::ffi::c_char;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::time::Duration;
+
+use super::session::Session;
+use super::signals::{self, SIGWINCH};
+use super::winsize::winsize;
+use crate::inputs::poll_fds;
+use crate::render_pipeline::headless::Headless;
+
+// from /usr/include/stdlib.h and /usr/include/fcntl.h
+extern "C" {
+    fn posix_openpt(__oflag: i32) -> i32;
+    fn grantpt(__fd: i32) -> i32;
+    fn unlockpt(__fd: i32) -> i32;
+    fn ptsname(__fd: i32) -> *const c_char;
+    fn open(__file: *const c_char, __oflag: i32, ...) -> i32;
+}
+
+// from /usr/include/asm-generic/fcntl.h
+const O_RDWR: i32 = 0o2;
+const O_NOCTTY: i32 = 0o400;
+
+// how long output reads wait for more bytes once the master went quiet
+const SETTLE: Duration = Duration::from_millis(20);
+
+/// a pseudo terminal pair, to run bararaq against without a real terminal
+/// the slave side is what the program sees as its terminal: it is where a Session
+/// sets its modes, where inputs get read from and where rendering writes to
+/// the master side is the other end of the wire: the bytes sent to it are what the user types,
+/// and the bytes read from it are what the terminal would display
+/// both sides are closed when the pty is dropped
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use bararaq::console::pty::Pty;
+/// use bararaq::inputs::{poll_events, Decoder};
+///
+/// let pty = Pty::open().unwrap();
+/// let session = pty.session().raw_mode();
+///
+/// let (mut reader, mut decoder) = (pty.reader().unwrap(), Decoder::new());
+/// pty.send(b"\x1b[A");
+/// let events = poll_events(&mut reader, &mut decoder, Some(Duration::from_secs(1))).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Pty {
+    master: File,
+    slave: File,
+}
+
+/// a buffered reader over the slave side of a pty
+/// the pty counterpart of the stdin lock, to be passed to the inputs read functions
+#[derive(Debug)]
+pub struct PtyReader(BufReader<File>);
+
+impl Read for PtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for PtyReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl AsRawFd for PtyReader {
+    fn as_raw_fd(&self) -> i32 {
+        self.0.get_ref().as_raw_fd()
+    }
+}
+
+impl Pty {
+    /// opens a new pseudo terminal pair
+    /// the slave starts in cooked mode with a 0x0 window size
+    pub fn open() -> std::io::Result<Self> {
+        let last_error = std::io::Error::last_os_error;
+
+        let master = unsafe { posix_openpt(O_RDWR | O_NOCTTY) };
+        if master < 0 {
+            return Err(last_error());
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+
+        if unsafe { grantpt(master.as_raw_fd()) } != 0
+            || unsafe { unlockpt(master.as_raw_fd()) } != 0
+        {
+            return Err(last_error());
+        }
+
+        let name = unsafe { ptsname(master.as_raw_fd()) };
+        if name.is_null() {
+            return Err(last_error());
+        }
+        let slave = unsafe { open(name, O_RDWR | O_NOCTTY) };
+        if slave < 0 {
+            return Err(last_error());
+        }
+        let slave = unsafe { File::from_raw_fd(slave) };
+
+        Ok(Self { master, slave })
+    }
+
+    /// the fd of the master side
+    pub fn master(&self) -> i32 {
+        self.master.as_raw_fd()
+    }
+
+    /// the fd of the slave side
+    pub fn slave(&self) -> i32 {
+        self.slave.as_raw_fd()
+    }
+
+    /// makes a session for the slave side
+    /// the session modes get set on and undone on the slave, not on stdin and stdout
+    pub fn session(&self) -> Session {
+        Session::on(self.slave(), self.slave())
+    }
+
+    /// returns a reader over the slave side, to read inputs from
+    pub fn reader(&self) -> std::io::Result<PtyReader> {
+        Ok(PtyReader(BufReader::new(self.slave.try_clone()?)))
+    }
+
+    /// returns a writer to the slave side, to render to
+    pub fn writer(&self) -> std::io::Result<File> {
+        self.slave.try_clone()
+    }
+
+    /// sends the given bytes as if they were typed into the terminal
+    /// e.g., b"a", b"\x1b[A" for the up arrow or b"\x1b[M !!" for a left click
+    pub fn send(&self, bytes: &[u8]) {
+        _ = (&self.master).write_all(bytes);
+    }
+
+    /// resizes the terminal window
+    /// a real resize makes the kernel signal the pty's foreground process group,
+    /// which the program running the pty is not part of, so SIGWINCH is raised here instead
+    /// and poll_events reports the new size as it would for a real terminal
+    pub fn resize(&self, cols: u16, rows: u16) {
+        winsize::with_size(cols, rows).set(self.master());
+        if signals::signals_fd().is_some() {
+            signals::raise(SIGWINCH);
+        }
+    }
+
+    /// the current window size of the terminal, as [cols, rows]
+    pub fn size(&self) -> [u16; 2] {
+        let ws = winsize::from_fd(self.slave());
+
+        [ws.cols(), ws.rows()]
+    }
+
+    /// reads everything that was written to the slave side since the last call
+    /// waits at most for the given timeout for the first bytes to arrive,
+    /// then keeps reading until the output settles
+    pub fn output(&self, timeout: Duration) -> Vec<u8> {
+        let mut v = vec![];
+        let mut buf = [0u8; 4096];
+        let mut wait = timeout;
+        while poll_fds(&[self.master()], Some(wait)).is_ok_and(|fds| !fds.is_empty()) {
+            match (&self.master).read(&mut buf) {
+                Ok(n) if n > 0 => v.extend_from_slice(&buf[..n]),
+                _ => break,
+            }
+            wait = SETTLE;
+        }
+
+        v
+    }
+
+    /// returns a headless screen of the current window size
+    /// holding everything that was written to the slave side since the last output read
+    pub fn screen(&self, timeout: Duration) -> Headless {
+        let [cols, rows] = self.size();
+        let mut screen = Headless::new(cols, rows);
+        _ = screen.write(&self.output(timeout));
+
+        screen
+    }
+}
+
+#[cfg(test)]
+mod pty {
+    use super::Pty;
+    use crate::console::raw_mode::termios;
+    use crate::inputs::keyboard::{Char, KbdEvent, CC};
+    use crate::inputs::window::WindowEvent;
+    use crate::inputs::{poll_events, Decoder, Interaction};
+    use std::io::Write;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    // sessions and the signals pipe are global, so the tests take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn inputs() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        let _session = pty.session().raw_mode();
+        let (mut reader, mut decoder) = (pty.reader().unwrap(), Decoder::new());
+
+        pty.send(b"a");
+        let events = poll_events(&mut reader, &mut decoder, Some(TIMEOUT)).unwrap();
+        assert!(matches!(
+            events[..],
+            [crate::inputs::InputEvent {
+                event: Interaction::KbdEvent(KbdEvent {
+                    char: Char::Char('a'),
+                    ..
+                }),
+                ..
+            }]
+        ));
+
+        pty.send(b"\x1b[A");
+        let events = poll_events(&mut reader, &mut decoder, Some(TIMEOUT)).unwrap();
+        assert!(matches!(
+            events[0].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::CC(CC::Up),
+                ..
+            })
+        ));
+
+        pty.send(b"\x1b[M !!");
+        let events = poll_events(&mut reader, &mut decoder, Some(TIMEOUT)).unwrap();
+        assert!(matches!(events[0].event, Interaction::MouseEvent(_)));
+
+        // nothing was sent
+        let events = poll_events(&mut reader, &mut decoder, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn resize() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        let (mut reader, mut decoder) = (pty.reader().unwrap(), Decoder::new());
+
+        // the first poll installs the SIGWINCH handler
+        poll_events(&mut reader, &mut decoder, Some(Duration::ZERO)).unwrap();
+        pty.resize(100, 40);
+        assert_eq!(pty.size(), [100, 40]);
+
+        let events = poll_events(&mut reader, &mut decoder, Some(TIMEOUT)).unwrap();
+        assert!(matches!(
+            events[0].event,
+            Interaction::WindowEvent(WindowEvent::WindowResized {
+                cols: 100,
+                rows: 40
+            })
+        ));
+    }
+
+    #[test]
+    fn session() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        let mut writer = pty.writer().unwrap();
+        let original = termios::from_fd(pty.slave()).unwrap();
+
+        let session = pty
+            .session()
+            .raw_mode()
+            .alternate_screen(&mut writer)
+            .mouse_input(&mut writer);
+        assert_ne!(termios::from_fd(pty.slave()).unwrap(), original);
+        let entered = pty.output(TIMEOUT);
+        assert!(entered.starts_with(b"\x1b[?1049h"));
+        assert!(entered.ends_with(b"\x1b[?1003h"));
+
+        drop(session);
+        assert_eq!(termios::from_fd(pty.slave()).unwrap(), original);
+        assert_eq!(pty.output(TIMEOUT), b"\x1b[?1003l\x1b[?1049l");
+    }
+
+    #[test]
+    fn render() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        pty.resize(12, 3);
+        let mut writer = pty.writer().unwrap();
+
+        write!(writer, "\x1b[2;3f\x1b[1mbararaq\x1b[0m\x1b[3;1fok").unwrap();
+        let screen = pty.screen(TIMEOUT);
+
+        screen.assert_snapshot(
+            "
+
+  bararaq
+ok",
+        );
+        assert!(screen.cell(2, 1).style.is_bold());
+    }
+
+    #[test]
+    fn cursor_position() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        let _session = pty.session().raw_mode();
+        let (mut reader, mut writer) = (pty.reader().unwrap(), pty.writer().unwrap());
+
+        // the reply comes after a key that was typed before the query
+        pty.send(b"a\x1b[12;5R");
+        assert_eq!(
+            crate::console::cursor_position(&mut reader, &mut writer, TIMEOUT),
+            Some([5, 12])
+        );
+        assert_eq!(pty.output(TIMEOUT), b"\x1b[6n");
+
+        // no reply
+        assert_eq!(
+            crate::console::cursor_position(&mut reader, &mut writer, Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn hang_up() {
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let pty = Pty::open().unwrap();
+        let (mut reader, mut decoder) = (pty.reader().unwrap(), Decoder::new());
+
+        // closing the master side hangs the slave side up
+        drop(pty);
+        assert!(poll_events(&mut reader, &mut decoder, Some(TIMEOUT)).is_err());
+    }
+}
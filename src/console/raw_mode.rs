@@ -1,5 +1,6 @@
 use std::io::BufRead;
 
+use std::io;
 use std::io::Read;
 use std::io::Write;
 // from /usr/include/termios.h
@@ -8,9 +9,11 @@ extern "C" {
     fn tcsetattr(__fd: i32, __optional_actions: i32, __termios_p: *const termios) -> i32;
 }
 
-/// ffi bindings for the c struct found here 'from /usr/include/asm-generic/termbits.h'
+/// ffi bindings for the c struct found here 'from /usr/include/bits/termios-struct.h'
+/// this is the libc struct, not the kernel one from '/usr/include/asm-generic/termbits.h';
+/// glibc and musl share the same layout on linux
 /// termios can be used to manipulate terminal flags
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 #[repr(C)]
 pub struct termios {
     c_iflag: tcflag_t,
@@ -18,13 +21,19 @@ pub struct termios {
     c_cflag: tcflag_t,
     c_lflag: tcflag_t,
     c_line: cc_t,
-    c_cc: cc_t,
+    c_cc: [cc_t; NCCS],
+    c_ispeed: speed_t,
+    c_ospeed: speed_t,
 }
 
-// from /usr/include/libr/sflib/common/sftypes.h
-type cc_t = [u8; 8];
+// from /usr/include/bits/termios.h
+type cc_t = u8;
+type speed_t = u32;
 type tcflag_t = u32;
 
+// from /usr/include/bits/termios-struct.h
+const NCCS: usize = 32;
+
 // from /usr/include/libr/sflib/common/sftypes.h
 const TCSANOW: i32 = 0;
 const TCSADRAIN: i32 = 1;
@@ -40,74 +49,346 @@ const STDERR_FILENO: i32 = 2;
 // flags definitions can be found in these header files: '/usr/include/bits/termios-c_*.h'
 // Recommended to read [this]('https://smnd.sk/anino/programming/c/unix_examples/raw.html') if you want to understand the reasoning behind the particular flag configuration of raw_mode
 
+// NOTE: the headers write the flags in octal, so do we
+
 // # [important]("https://www.gnu.org/software/libc/manual/html_node/Terminal-Modes.html")
 // # lflag bits
 // turning this flag off means input won't be displayed on the terminal anymore
-const ECHO: u32 = 10;
+const ECHO: u32 = 0o10;
 // turning this flag off disables the INTR, QUIT and SUSP signals
 // basically, when this is off, stuff like ctrl-c (SIGINT) won't work
 // turning this bit off renders c_cc's special characters off
-const ISIG: u32 = 1;
+const ISIG: u32 = 0o1;
 // canonical input mode gives meaning to escape sequences,
 // without it 'Backspace' would not delete the char behind cursor and 'Enter' will not submit input
-const ICANON: u32 = 2;
-//
-const IEXTEN: u32 = 100_000;
+const ICANON: u32 = 0o2;
+// implementation defined input processing, e.g., ctrl-v (literal next) and ctrl-o (discard)
+const IEXTEN: u32 = 0o100000;
 
 // # iflag bits
 // disabling this disables signal interrupt on break, we don't want it in raw mode
-const BRKINT: u32 = 2;
+const BRKINT: u32 = 0o2;
 // when this is enabled input is checked for parity,
 // this bit is a pair with cflag's PARENB bit
-const INPCK: u32 = 20;
+const INPCK: u32 = 0o20;
 // this strips the 8th bit off an input ascii char / byte
-const ISTRIP: u32 = 40;
+const ISTRIP: u32 = 0o40;
 // maps LF to CR
-const INLCR: u32 = 100;
+const INLCR: u32 = 0o100;
 // this bit maps CR to NL, meaning that it automatically prepends a cr (\r) on lf (\n)
-const ICRNL: u32 = 400;
+const ICRNL: u32 = 0o400;
 // machine ouput start/stop control
 // we'll turn this off, but keep its input counterpart
-const IXON: u32 = 2000;
+const IXON: u32 = 0o2000;
 
 // # oflag bits
 // this bit enables output post processing, processes output before displaying it so that it is
 // rendered correctly on the terminal
-const OPOST: u32 = 1;
+const OPOST: u32 = 0o1;
 
 // # cflag bits
+// the character size mask, CS8 is one of its values
+const CSIZE: u32 = 0o60;
 // specifies that a byte will be 8 bits
-const CS8: u32 = 60;
+const CS8: u32 = 0o60;
 // when this is enabled a parity bit is added to output values
-const PARENB: u32 = 400;
+const PARENB: u32 = 0o400;
+
+// from /usr/include/bits/termios-c_cc.h
+// indices of the c_cc array
+// in non canonical mode, VTIME is the read timeout in deciseconds
+const VTIME: usize = 5;
+// in non canonical mode, VMIN is the minimum number of bytes a read waits for
+const VMIN: usize = 6;
+
+/// a configurable terminal mode
+/// decides which of the terminal termios flags get turned on or off when the mode is applied
+/// comes with the raw, cbreak and cooked presets
+///
+/// # Examples
+/// ```no_run
+/// use bararaq::console::{cooked_mode, TermMode};
+///
+/// // raw mode, but ctrl-c still sends SIGINT and '\n' still starts a new line
+/// let original = TermMode::raw().signals(true).output_processing(true).apply().unwrap();
+/// // ...
+/// cooked_mode(original);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermMode {
+    echo: bool,
+    canonical: bool,
+    signals: bool,
+    input_processing: bool,
+    output_processing: bool,
+    vmin: u8,
+    vtime: u8,
+}
+
+impl TermMode {
+    /// nothing is processed by the terminal, input is read byte by byte as it arrives
+    pub fn raw() -> Self {
+        Self {
+            echo: false,
+            canonical: false,
+            signals: false,
+            input_processing: false,
+            output_processing: false,
+            vmin: 1,
+            vtime: 0,
+        }
+    }
+
+    /// also known as rare mode
+    /// input is read byte by byte without echo, like raw mode,
+    /// but ctrl-c, ctrl-z and ctrl-\ still raise their signals
+    /// and output is still post processed, so '\n' goes back to the start of the line
+    pub fn cbreak() -> Self {
+        Self {
+            signals: true,
+            output_processing: true,
+            ..Self::raw()
+        }
+    }
+
+    /// the terminal's normal mode; line buffered input with echo and signals
+    /// also gives back the flags raw mode takes off, e.g., BRKINT,
+    /// so going from raw to cooked makes a cooked terminal;
+    /// only restoring the original termios gives back the exact flags the terminal had
+    pub fn cooked() -> Self {
+        Self {
+            echo: true,
+            canonical: true,
+            signals: true,
+            input_processing: true,
+            output_processing: true,
+            vmin: 1,
+            vtime: 0,
+        }
+    }
+
+    /// whether typed input is displayed on the terminal
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// whether input is line buffered and line edited by the terminal
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// whether ctrl-c, ctrl-z and ctrl-\ raise SIGINT, SIGTSTP and SIGQUIT
+    /// instead of being read as input
+    pub fn signals(mut self, signals: bool) -> Self {
+        self.signals = signals;
+        self
+    }
+
+    /// whether '\r' gets mapped to '\n' on input and ctrl-s/ctrl-q do flow control
+    pub fn input_processing(mut self, input_processing: bool) -> Self {
+        self.input_processing = input_processing;
+        self
+    }
+
+    /// whether output gets post processed, e.g., '\n' being turned into '\r\n'
+    pub fn output_processing(mut self, output_processing: bool) -> Self {
+        self.output_processing = output_processing;
+        self
+    }
+
+    /// the read semantics of non canonical mode
+    /// vmin is the minimum number of bytes a read waits for,
+    /// vtime is how long a read waits for input, in deciseconds
+    /// vmin = 1, vtime = 0 blocks until at least 1 byte arrives
+    /// vmin = 0, vtime = 0 never blocks
+    pub fn read(mut self, vmin: u8, vtime: u8) -> Self {
+        self.vmin = vmin;
+        self.vtime = vtime;
+        self
+    }
+
+    // makes this mode's termios out of the original one
+    fn termios(&self, original: &termios) -> termios {
+        let mut t = original.clone();
+
+        let toggle = |flags: &mut tcflag_t, bits: tcflag_t, on: bool| {
+            if on {
+                *flags |= bits;
+            } else {
+                *flags &= !bits;
+            }
+        };
+
+        toggle(&mut t.c_lflag, ECHO, self.echo);
+        toggle(&mut t.c_lflag, ICANON | IEXTEN, self.canonical);
+        toggle(&mut t.c_lflag, ISIG, self.signals);
+        toggle(&mut t.c_iflag, ICRNL | IXON, self.input_processing);
+        toggle(&mut t.c_oflag, OPOST, self.output_processing);
+        // a break raises SIGINT in canonical mode only, as stty sane has it
+        toggle(&mut t.c_iflag, BRKINT, self.canonical);
+
+        // the parity and 8th bit stripping flags have nothing to do with a terminal emulator,
+        // they are off on a fresh terminal too
+        t.c_iflag &= !(INPCK | ISTRIP | INLCR);
+        t.c_cflag &= !(PARENB | CSIZE);
+        t.c_cflag |= CS8;
+        // canonical mode ignores these, cooked gives back the fresh terminal's 1 and 0
+        t.c_cc[VMIN] = self.vmin;
+        t.c_cc[VTIME] = self.vtime;
+
+        t
+    }
+
+    /// applies this mode to the terminal
+    /// returns the original terminal flags in a termios instance for use when going back to
+    /// cooked mode
+    ///
+    /// # Errors
+    /// the same as apply_to
+    pub fn apply(&self) -> io::Result<termios> {
+        self.apply_to(STDIN_FILENO)
+    }
+
+    /// applies this mode to the terminal behind the given fd
+    /// returns the original terminal flags of that terminal
+    ///
+    /// # Errors
+    /// returns an error if the flags could not be read or set,
+    /// e.g., the fd is not a terminal; nothing is applied then
+    pub fn apply_to(&self, fd: i32) -> io::Result<termios> {
+        let original = termios::from_fd(fd)?;
+        if self.termios(&original).set(fd) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(original)
+    }
+}
+
+impl termios {
+    /// reads the current flags of the terminal behind the given fd
+    ///
+    /// # Errors
+    /// returns an error if the fd is not a terminal
+    pub fn from_fd(fd: i32) -> io::Result<Self> {
+        let mut t = Self::default();
+        if unsafe { tcgetattr(fd, &mut t) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(t)
+    }
+
+    /// sets these flags on the terminal behind the given fd
+    /// returns the tcsetattr result; 0 on success, -1 on error
+    pub fn set(&self, fd: i32) -> i32 {
+        unsafe { tcsetattr(fd, TCSANOW, self) }
+    }
+}
 
 /// enables raw mode through disabling the relevant terminal flags - mainly ECHO and CANONICAL mode
 /// returns the original terminal flags in a termios instance for use when disabling raw mode
-/// read the source code to know more about what gets disabled
-pub fn raw_mode() -> termios {
-    unsafe {
-        let mut original: termios = std::mem::zeroed();
-        let _res = tcgetattr(STDIN_FILENO, &mut original);
-        let mut raw = original.clone();
-        raw.c_lflag &= !(ISIG | ICANON | ECHO | IEXTEN);
-        raw.c_iflag &= !(BRKINT | INPCK | ISTRIP | INLCR | ICRNL | IXON);
-        raw.c_oflag &= !OPOST;
-        raw.c_cflag &= !PARENB;
-        raw.c_cflag |= CS8;
-
-        let _res = tcsetattr(STDIN_FILENO, TCSANOW, &raw);
-
-        original
-    }
+/// read TermMode::raw to know more about what gets disabled
+///
+/// # Errors
+/// returns an error if stdin is not a terminal, see TermMode::apply_to
+pub fn raw_mode() -> io::Result<termios> {
+    TermMode::raw().apply()
 }
 
 /// disables raw mode to original flags configuration
 /// takes the original flags from the 'original' termios instance returned from enable_raw_mode
 pub fn cooked_mode(original: termios) -> i32 {
-    unsafe { tcsetattr(STDIN_FILENO, TCSANOW, &original) }
+    original.set(STDIN_FILENO)
 }
 
 /// enables rare mode
 /// a terminal mode that is between cooked and raw
 /// in terms of features and functionality
-fn rare_mode() {}
+/// returns the original terminal flags, like raw_mode
+///
+/// # Errors
+/// the same as raw_mode
+pub fn rare_mode() -> io::Result<termios> {
+    TermMode::cbreak().apply()
+}
+
+#[cfg(test)]
+mod termios_ffi {
+    use super::*;
+    use crate::console::pty::Pty;
+
+    #[test]
+    fn layout() {
+        // sizeof(struct termios) on linux glibc and musl
+        assert_eq!(std::mem::size_of::<termios>(), 60);
+    }
+
+    #[test]
+    fn round_trip() {
+        let pty = Pty::open().unwrap();
+        let slave = pty.slave();
+
+        let original = termios::from_fd(slave).unwrap();
+        // a fresh pty starts in cooked mode
+        assert_ne!(original.c_lflag & ECHO, 0);
+        assert_ne!(original.c_lflag & ICANON, 0);
+        assert_eq!(original.set(slave), 0);
+        assert_eq!(termios::from_fd(slave).unwrap(), original);
+    }
+
+    #[test]
+    fn raw() {
+        let pty = Pty::open().unwrap();
+        let slave = pty.slave();
+
+        let original = TermMode::raw().apply_to(slave).unwrap();
+        let raw = termios::from_fd(slave).unwrap();
+        assert_eq!(raw.c_lflag & (ECHO | ICANON | IEXTEN | ISIG), 0);
+        assert_eq!(raw.c_iflag & (ICRNL | IXON), 0);
+        assert_eq!(raw.c_oflag & OPOST, 0);
+        assert_eq!(raw.c_cflag & CSIZE, CS8);
+        assert_eq!([raw.c_cc[VMIN], raw.c_cc[VTIME]], [1, 0]);
+
+        // cooked gives back what raw took off
+        TermMode::cooked().apply_to(slave).unwrap();
+        let cooked = termios::from_fd(slave).unwrap();
+        let lflags = ECHO | ICANON | IEXTEN | ISIG;
+        assert_eq!(cooked.c_lflag & lflags, lflags);
+        assert_eq!(
+            cooked.c_iflag & (BRKINT | ICRNL | IXON),
+            BRKINT | ICRNL | IXON
+        );
+        assert_eq!(cooked.c_oflag & OPOST, OPOST);
+
+        assert_eq!(original.set(slave), 0);
+        assert_eq!(termios::from_fd(slave).unwrap(), original);
+    }
+
+    #[test]
+    fn not_a_terminal() {
+        assert!(termios::from_fd(-1).is_err());
+        assert!(TermMode::raw().apply_to(-1).is_err());
+    }
+
+    #[test]
+    fn cbreak() {
+        let pty = Pty::open().unwrap();
+        let slave = pty.slave();
+
+        let original = TermMode::cbreak().apply_to(slave).unwrap();
+        let cbreak = termios::from_fd(slave).unwrap();
+        assert_eq!(cbreak.c_lflag & (ECHO | ICANON), 0);
+        assert_ne!(cbreak.c_lflag & ISIG, 0);
+        assert_ne!(cbreak.c_oflag & OPOST, 0);
+
+        TermMode::cooked().apply_to(slave).unwrap();
+        let cooked = termios::from_fd(slave).unwrap();
+        assert_ne!(cooked.c_lflag & ECHO, 0);
+        assert_ne!(cooked.c_lflag & ICANON, 0);
+
+        original.set(slave);
+    }
+}
@@ -3,7 +3,7 @@ use std::io::Write;
 use crate::components::*;
 
 impl Container {
-    pub fn scroll_on(&self, writer: &mut std::io::StdoutLock) {
+    pub fn scroll_on(&self, writer: &mut impl Write) {
         if self.attributes.contains("scrollable") {
             writer.write(format!("\x1b[{};{}r", self.vpos, self.h + self.vpos).as_bytes());
         }
@@ -0,0 +1,425 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once, TryLockError};
+
+use super::raw_mode::{termios, TermMode};
+use super::signals::{self, SIGCONT, SIGTSTP};
+use super::title::{push_title, set_title};
+use super::{enable_bracketed_paste, enter_alternate_screen};
+use crate::inputs::kitty::{disable_kitty_keyboard, enable_kitty_keyboard};
+use crate::inputs::mouse::enable_mouse_input;
+use crate::inputs::window::enable_focus_input;
+use crate::inputs::xterm::enable_modify_other_keys;
+
+// from /usr/include/unistd.h
+extern "C" {
+    fn write(__fd: i32, __buf: *const u8, __n: usize) -> isize;
+}
+
+// from /usr/include/unistd.h
+const STDIN_FILENO: i32 = 0;
+const STDOUT_FILENO: i32 = 1;
+
+// the terminal modes that are currently turned on by a Session
+// this is global since the terminal itself is global,
+// and the panic hook has to be able to reach it
+struct Modes {
+    /// the fd the terminal flags are set on, stdin unless the session was made with Session::on
+    input: i32,
+    /// the fd the mode sequences are written to on restore, stdout unless made with Session::on
+    output: i32,
+    /// the original termios, if raw mode was turned on
+    original: Option<termios>,
+    /// the termios that the session applied, re-applied on resume
+    applied: Option<termios>,
+    alternate_screen: bool,
+    mouse: bool,
+    bracketed_paste: bool,
+    focus: bool,
+    /// the title the session set, the user's title is pushed on the title stack meanwhile
+    title: Option<String>,
+    other_keys: bool,
+    /// the kitty keyboard protocol flags the session pushed
+    kitty: Option<u8>,
+    /// whether ctrl-z suspends the program
+    job_control: bool,
+}
+
+static MODES: Mutex<Modes> = Mutex::new(Modes {
+    input: STDIN_FILENO,
+    output: STDOUT_FILENO,
+    original: None,
+    applied: None,
+    alternate_screen: false,
+    mouse: false,
+    bracketed_paste: false,
+    focus: false,
+    title: None,
+    other_keys: false,
+    kitty: None,
+    job_control: false,
+});
+
+static PANIC_HOOK: Once = Once::new();
+
+// whether suspend already re-applied the modes for the SIGCONT that is still queued
+static RESUMED: AtomicBool = AtomicBool::new(false);
+
+fn modes() -> MutexGuard<'static, Modes> {
+    // a panic while holding the lock must not stop us from restoring the terminal
+    MODES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// writes straight to the output fd, since a panicking thread
+// may not be able to take the stdout lock
+fn write_fd(fd: i32, s: &str) {
+    if !s.is_empty() {
+        _ = unsafe { write(fd, s.as_ptr(), s.len()) };
+    }
+}
+
+// the sequences that turn the modes on
+fn enter_sequences(modes: &Modes) -> String {
+    let mut s = String::new();
+    if modes.alternate_screen {
+        s.push_str("\x1b[?1049h\x1b[0;0f");
+    }
+    if modes.mouse {
+        s.push_str("\x1b[?1003h");
+    }
+    if modes.bracketed_paste {
+        s.push_str("\x1b[?2004h");
+    }
+    if modes.focus {
+        s.push_str("\x1b[?1004h");
+    }
+    if let Some(title) = &modes.title {
+        let mut v = b"\x1b[22;0t".to_vec();
+        set_title(&mut v, title);
+        s.push_str(&String::from_utf8_lossy(&v));
+    }
+    if modes.other_keys {
+        s.push_str("\x1b[>4;2m");
+    }
+    // the alternate screen has its own keyboard mode stack, so this comes after entering it
+    if let Some(flags) = modes.kitty {
+        s.push_str(&format!("\x1b[>{}u", flags));
+    }
+
+    s
+}
+
+// the sequences that turn the modes off, in the reverse order of enter_sequences
+fn leave_sequences(modes: &Modes) -> String {
+    let mut s = String::new();
+    if modes.kitty.is_some() {
+        s.push_str("\x1b[<u");
+    }
+    if modes.other_keys {
+        s.push_str("\x1b[>4m");
+    }
+    if modes.title.is_some() {
+        s.push_str("\x1b[23;0t");
+    }
+    if modes.focus {
+        s.push_str("\x1b[?1004l");
+    }
+    if modes.bracketed_paste {
+        s.push_str("\x1b[?2004l");
+    }
+    if modes.mouse {
+        s.push_str("\x1b[?1003l");
+    }
+    if modes.alternate_screen {
+        s.push_str("\x1b[?1049l");
+    }
+
+    s
+}
+
+/// undoes every mode that is still turned on
+fn restore() {
+    undo(&mut modes());
+}
+
+// the panic hook can't wait on the lock, the panicking thread may be the one holding it,
+// e.g., a panic inside enter_sequences; the session drop restores the terminal then
+fn try_restore() {
+    match MODES.try_lock() {
+        Ok(mut modes) => undo(&mut modes),
+        Err(TryLockError::Poisoned(e)) => undo(&mut e.into_inner()),
+        Err(TryLockError::WouldBlock) => (),
+    }
+}
+
+fn undo(modes: &mut Modes) {
+    write_fd(modes.output, &leave_sequences(modes));
+    modes.mouse = false;
+    modes.bracketed_paste = false;
+    modes.focus = false;
+    modes.title = None;
+    modes.kitty = None;
+    modes.other_keys = false;
+    modes.alternate_screen = false;
+    modes.applied = None;
+
+    if std::mem::take(&mut modes.job_control) {
+        signals::reset(SIGTSTP);
+    }
+
+    if let Some(original) = modes.original.take() {
+        original.set(modes.input);
+    }
+
+    modes.input = STDIN_FILENO;
+    modes.output = STDOUT_FILENO;
+}
+
+/// suspends the program, the same way ctrl-z does in a cooked mode terminal
+/// the terminal is first put back in its original mode on the primary screen,
+/// then the program gets stopped until the shell continues it (e.g., through 'fg')
+/// once continued, the session modes are re-applied before this function returns
+/// since the screen content is lost, the program should redraw everything afterwards;
+/// poll_events reports a WindowResumed event for that
+pub fn suspend() {
+    _ = std::io::stdout().flush();
+    {
+        let modes = modes();
+        write_fd(modes.output, &leave_sequences(&modes));
+        if let Some(original) = &modes.original {
+            original.set(modes.input);
+        }
+    }
+
+    // our own SIGTSTP handler would only write to the signals pipe
+    // the default action is what actually stops the program
+    signals::reset(SIGTSTP);
+    signals::raise(SIGTSTP);
+    // execution continues from here on SIGCONT
+
+    resume();
+    // the SIGCONT only gets to the signals pipe if job control is on
+    if job_control() {
+        RESUMED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// re-applies the session modes after a SIGCONT, unless suspend already did
+/// the program may have been stopped by something else than suspend, e.g., a SIGSTOP
+pub fn continued() {
+    if !RESUMED.swap(false, Ordering::Relaxed) {
+        resume();
+    }
+}
+
+/// re-applies the session modes
+/// called after the program gets continued from a stop
+pub fn resume() {
+    let modes = modes();
+    if let Some(applied) = &modes.applied {
+        applied.set(modes.input);
+    }
+    write_fd(modes.output, &enter_sequences(&modes));
+
+    if modes.job_control {
+        signals::listen(SIGTSTP);
+    }
+}
+
+/// whether the running session has job control turned on
+pub fn job_control() -> bool {
+    modes().job_control
+}
+
+/// RAII guard over the terminal modes
+/// remembers which modes it turned on and turns them back off when dropped
+/// also installs a panic hook that restores the terminal before the panic message gets printed
+/// the modes are undone on stdout, whatever writer was used to turn them on,
+/// unless the session was made for another terminal through Session::on
+///
+/// # Examples
+/// ```no_run
+/// use bararaq::console::Session;
+///
+/// let mut writer = std::io::stdout().lock();
+/// let session = Session::new()
+///     .raw_mode()
+///     .alternate_screen(&mut writer)
+///     .mouse_input(&mut writer);
+///
+/// // the terminal is restored once session goes out of scope, even on panic
+/// ```
+#[derive(Debug)]
+pub struct Session {
+    // a session can only be made through Session::new
+    _private: (),
+}
+
+impl Session {
+    /// creates a new session with no modes turned on
+    /// installs the terminal restoring panic hook on the first call
+    pub fn new() -> Self {
+        PANIC_HOOK.call_once(|| {
+            let hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                try_restore();
+                hook(info);
+            }));
+        });
+
+        Self { _private: () }
+    }
+
+    /// creates a new session for the terminal behind the given fds instead of stdin and stdout
+    /// the terminal flags are set on the input fd and the modes are undone on the output fd;
+    /// e.g., the slave side of a pty::Pty
+    /// there is only one terminal per session, so this must not be mixed with a stdin session
+    pub fn on(input: i32, output: i32) -> Self {
+        let session = Self::new();
+
+        let mut modes = modes();
+        modes.input = input;
+        modes.output = output;
+
+        drop(modes);
+        session
+    }
+
+    /// turns raw mode on
+    /// the original terminal flags are saved to be restored on drop
+    pub fn raw_mode(self) -> Self {
+        self.mode(&TermMode::raw())
+    }
+
+    /// applies the given terminal mode
+    /// the original terminal flags are saved to be restored on drop,
+    /// applying more modes afterwards does not overwrite them
+    pub fn mode(self, mode: &TermMode) -> Self {
+        let mut modes = modes();
+        // an input that is not a terminal has no mode to apply
+        if let Ok(original) = mode.apply_to(modes.input) {
+            if modes.original.is_none() {
+                modes.original = Some(original);
+            }
+            modes.applied = termios::from_fd(modes.input).ok();
+        }
+
+        drop(modes);
+        self
+    }
+
+    /// moves to the terminal alternate screen
+    pub fn alternate_screen(self, writer: &mut impl Write) -> Self {
+        enter_alternate_screen(writer);
+        modes().alternate_screen = true;
+
+        self
+    }
+
+    /// enables raw mouse input reading
+    pub fn mouse_input(self, writer: &mut impl Write) -> Self {
+        enable_mouse_input(writer);
+        _ = writer.flush();
+        modes().mouse = true;
+
+        self
+    }
+
+    /// turns bracketed paste on
+    /// pastes then come as a single PasteEvent that holds the exact pasted text
+    pub fn bracketed_paste(self, writer: &mut impl Write) -> Self {
+        enable_bracketed_paste(writer);
+        _ = writer.flush();
+        modes().bracketed_paste = true;
+
+        self
+    }
+
+    /// enables focus input reporting
+    /// the window gaining and losing focus then come as WindowGainedFocus and WindowLostFocus events
+    pub fn focus_input(self, writer: &mut impl Write) -> Self {
+        enable_focus_input(writer);
+        _ = writer.flush();
+        modes().focus = true;
+
+        self
+    }
+
+    /// sets the window title
+    /// the user's title is pushed on the terminal's title stack the first time
+    /// and gets popped back on restore, and while the program is suspended
+    pub fn title(self, writer: &mut impl Write, title: &str) -> Self {
+        let mut modes = modes();
+        if modes.title.is_none() {
+            push_title(writer);
+        }
+        set_title(writer, title);
+        _ = writer.flush();
+        modes.title = Some(title.to_string());
+
+        drop(modes);
+        self
+    }
+
+    /// turns xterm's modifyOtherKeys on
+    /// shortcuts like ctrl+shift+letter or ctrl+punctuation then come as KbdEvents
+    /// with the right modifiers, on terminals without the kitty keyboard protocol
+    pub fn modify_other_keys(self, writer: &mut impl Write) -> Self {
+        enable_modify_other_keys(writer);
+        _ = writer.flush();
+        modes().other_keys = true;
+
+        self
+    }
+
+    /// pushes the given kitty keyboard protocol flags, see inputs::kitty for the flags
+    /// keys then come as unambiguous KbdEvents, with release events if asked for
+    /// terminals without the protocol keep sending the legacy sequences
+    /// the flags are popped on restore, and while the program is suspended
+    pub fn kitty_keyboard(self, writer: &mut impl Write, flags: u8) -> Self {
+        let mut modes = modes();
+        if modes.kitty.is_some() {
+            disable_kitty_keyboard(writer);
+        }
+        enable_kitty_keyboard(writer, flags);
+        _ = writer.flush();
+        modes.kitty = Some(flags);
+
+        drop(modes);
+        self
+    }
+
+    /// turns job control on
+    /// ctrl-z suspends the program, through the console suspend function,
+    /// whether it arrives as SIGTSTP (when the mode keeps signals on) or as input (raw mode)
+    /// continuing the program re-applies the session modes
+    /// and poll_events then reports a WindowResumed event so the program can redraw
+    pub fn job_control(self) -> Self {
+        signals::listen(SIGTSTP);
+        signals::listen(SIGCONT);
+        modes().job_control = true;
+
+        self
+    }
+
+    /// restores the terminal to the state it was in before this session
+    /// this is automatically called when the session is dropped
+    pub fn restore(&mut self) {
+        // anything the program wrote before this has to reach the terminal
+        // before we leave the alternate screen
+        _ = std::io::stdout().flush();
+        restore();
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::sync::Once;
+
+// from /usr/include/signal.h and /usr/include/unistd.h
+extern "C" {
+    fn signal(__sig: i32, __handler: usize) -> usize;
+    #[link_name = "raise"]
+    fn c_raise(__sig: i32) -> i32;
+    fn pipe2(__pipedes: *mut i32, __flags: i32) -> i32;
+    fn read(__fd: i32, __buf: *mut u8, __nbytes: usize) -> isize;
+    fn write(__fd: i32, __buf: *const u8, __n: usize) -> isize;
+}
+
+// from /usr/include/errno.h
+extern "C" {
+    fn __errno_location() -> *mut i32;
+}
+
+// from /usr/include/bits/signum-generic.h and /usr/include/bits/signum-arch.h
+/// window size change
+pub const SIGWINCH: i32 = 28;
+/// keyboard stop, ctrl-z
+pub const SIGTSTP: i32 = 20;
+/// continue if stopped
+pub const SIGCONT: i32 = 18;
+
+// from /usr/include/bits/signum-generic.h
+// the default action of a signal
+const SIG_DFL: usize = 0;
+
+// from /usr/include/bits/fcntl-linux.h
+const O_NONBLOCK: i32 = 0o4000;
+const O_CLOEXEC: i32 = 0o2000000;
+
+// the self-pipe; the signal handler writes the signal number to the write end
+// and the event loop polls the read end alongside stdin
+// -1 means that the pipe was not opened yet
+static PIPE_READ: AtomicI32 = AtomicI32::new(-1);
+static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+static PIPE: Once = Once::new();
+
+// the signals our handler is installed for, one bit per signal number
+static LISTENING: AtomicU64 = AtomicU64::new(0);
+
+// signal numbers go up to 64
+fn bit(sig: i32) -> u64 {
+    1 << (sig as u32 % 64)
+}
+
+// only async signal safe calls are allowed in here
+extern "C" fn handler(sig: i32) {
+    let fd = PIPE_WRITE.load(Ordering::Relaxed);
+    if fd != -1 {
+        // the interrupted code may be about to read errno, which write can change
+        let errno = unsafe { *__errno_location() };
+        let byte = sig as u8;
+        // if the pipe is full then the event loop is already lagging behind
+        // and will see the signals that are already queued
+        _ = unsafe { write(fd, &byte, 1) };
+        unsafe { *__errno_location() = errno };
+    }
+}
+
+fn open_pipe() -> Option<i32> {
+    PIPE.call_once(|| {
+        let mut fds = [-1; 2];
+        // neither end may block; the handler can't wait on a full pipe
+        // and draining stops when the pipe is empty
+        // neither end may leak into the programs we spawn either
+        if unsafe { pipe2(fds.as_mut_ptr(), O_NONBLOCK | O_CLOEXEC) } == -1 {
+            return;
+        }
+
+        PIPE_WRITE.store(fds[1], Ordering::Release);
+        PIPE_READ.store(fds[0], Ordering::Release);
+    });
+
+    signals_fd()
+}
+
+/// starts listening to the given signal
+/// from now on, every time the signal is raised its number gets written to the signals pipe
+/// returns the read end of the pipe, or None if the pipe could not be opened
+/// the handler is only installed once, listening again to the same signal is cheap
+pub fn listen(sig: i32) -> Option<i32> {
+    let fd = open_pipe()?;
+    if LISTENING.fetch_or(bit(sig), Ordering::AcqRel) & bit(sig) == 0 {
+        unsafe { signal(sig, handler as extern "C" fn(i32) as usize) };
+    }
+
+    Some(fd)
+}
+
+/// stops listening to the given signal and gives it back its default action
+pub fn reset(sig: i32) {
+    LISTENING.fetch_and(!bit(sig), Ordering::AcqRel);
+    unsafe { signal(sig, SIG_DFL) };
+}
+
+/// sends the given signal to this program
+pub fn raise(sig: i32) {
+    _ = unsafe { c_raise(sig) };
+}
+
+/// returns the read end of the signals pipe if any signal is being listened to
+/// the fd can be polled together with stdin, it becomes readable once a signal arrives
+pub fn signals_fd() -> Option<i32> {
+    match PIPE_READ.load(Ordering::Acquire) {
+        -1 => None,
+        fd => Some(fd),
+    }
+}
+
+/// drains the signals pipe and returns the numbers of the signals raised since the last call
+/// in the order they were raised
+/// never blocks
+pub fn pending() -> Vec<i32> {
+    let Some(fd) = signals_fd() else {
+        return vec![];
+    };
+
+    let mut v = vec![];
+    let mut buf = [0u8; 32];
+    loop {
+        let n = unsafe { read(fd, buf.as_mut_ptr(), buf.len()) };
+        if n <= 0 {
+            break;
+        }
+        v.extend(buf[..n as usize].iter().map(|b| *b as i32));
+    }
+
+    v
+}
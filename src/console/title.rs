@@ -0,0 +1,50 @@
+use std::io::Write;
+
+// xterm reads an OSC string up to BEL or ST,
+// so control chars in a title could end it early and inject sequences
+fn osc(writer: &mut impl Write, code: u8, s: &str) {
+    let s = s.chars().filter(|c| !c.is_control()).collect::<String>();
+    _ = writer.write(format!("\x1b]{};{}\x07", code, s).as_bytes());
+}
+
+/// sets both the window title and the icon name, OSC 0
+pub fn set_title_and_icon(writer: &mut impl Write, title: &str) {
+    osc(writer, 0, title);
+}
+
+/// sets the icon name, OSC 1
+/// most terminals show it as the tab title
+pub fn set_icon_name(writer: &mut impl Write, name: &str) {
+    osc(writer, 1, name);
+}
+
+/// sets the window title, OSC 2
+pub fn set_title(writer: &mut impl Write, title: &str) {
+    osc(writer, 2, title);
+}
+
+/// saves the current window title and icon name on the terminal's title stack, XTWINOPS 22
+/// to be restored later with pop_title, e.g., when the program exits
+pub fn push_title(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[22;0t");
+}
+
+/// restores the window title and icon name last saved with push_title, XTWINOPS 23
+pub fn pop_title(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[23;0t");
+}
+
+#[cfg(test)]
+mod title {
+    use super::{pop_title, push_title, set_title};
+
+    #[test]
+    fn sequences() {
+        let mut v = vec![];
+        push_title(&mut v);
+        set_title(&mut v, "bararaq\x07\x1b]2;evil");
+        pop_title(&mut v);
+
+        assert_eq!(v, b"\x1b[22;0t\x1b]2;bararaq]2;evil\x07\x1b[23;0t");
+    }
+}
@@ -36,12 +36,33 @@ const TIOCSWINSZ: u64 = 0x5414;
 impl winsize {
     /// creates a new winsize instance with the current window width and height
     pub fn from_ioctl() -> Self {
+        Self::from_fd(STDOUT_FILENO)
+    }
+
+    /// creates a new winsize instance with the window width and height of the terminal behind the given fd
+    pub fn from_fd(fd: i32) -> Self {
         let mut ws = Default::default();
-        _ = unsafe { ioctl(STDOUT_FILENO, TIOCGWINSZ, &mut ws) };
+        _ = unsafe { ioctl(fd, TIOCGWINSZ, &mut ws) };
 
         ws
     }
 
+    /// creates a new winsize instance with the given width and height
+    pub fn with_size(cols: u16, rows: u16) -> Self {
+        Self {
+            ws_row: rows,
+            ws_col: cols,
+            ..Default::default()
+        }
+    }
+
+    /// sets this window size on the terminal behind the given fd
+    /// the kernel then sends a SIGWINCH to the terminal's foreground process group
+    /// returns the ioctl result, 0 on success and -1 on error
+    pub fn set(&self, fd: i32) -> i32 {
+        unsafe { ioctl(fd, TIOCSWINSZ, self as *const Self) }
+    }
+
     /// returns the width of the terminal window from this winsize instane
     pub fn cols(&self) -> u16 {
         self.ws_col
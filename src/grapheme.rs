@@ -0,0 +1,280 @@
+mod tables;
+
+use tables::CLASSES;
+
+/// the most bytes a Grapheme holds
+/// enough for the longest emoji sequences, e.g., a family or a subdivision flag
+pub const CAPACITY: usize = 31;
+
+// the grapheme cluster break property of a char, from UAX #29
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Other,
+    CR,
+    LF,
+    Control,
+    Extend,
+    ZWJ,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    LV,
+    LVT,
+    Pictographic,
+}
+
+fn class(c: char) -> Class {
+    let c = c as u32;
+    match c {
+        0x0d => return Class::CR,
+        0x0a => return Class::LF,
+        0x200d => return Class::ZWJ,
+        // hangul syllables, each 28th one has no trailing consonant
+        0xac00..=0xd7a3 if (c - 0xac00).is_multiple_of(28) => return Class::LV,
+        0xac00..=0xd7a3 => return Class::LVT,
+        // ascii letters are the most common input by far
+        0x20..=0x7e => return Class::Other,
+        _ => (),
+    }
+
+    CLASSES
+        .binary_search_by(|(start, end, _)| {
+            if *end < c {
+                std::cmp::Ordering::Less
+            } else if *start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map_or(Class::Other, |idx| CLASSES[idx].2)
+}
+
+// whether there is a cluster boundary between prev and next
+// pict is whether prev ends an extended pictographic followed by extends and a zwj,
+// ris is the number of regional indicators in a row up to prev
+fn breaks(prev: Class, next: Class, pict: bool, ris: usize) -> bool {
+    use Class::*;
+
+    match (prev, next) {
+        (CR, LF) => false,
+        (Control | CR | LF, _) | (_, Control | CR | LF) => true,
+        // hangul syllables
+        (L, L | V | LV | LVT) => false,
+        (LV | V, V | T) => false,
+        (LVT | T, T) => false,
+        (_, Extend | ZWJ | SpacingMark) => false,
+        (Prepend, _) => false,
+        // emoji zwj sequences
+        (ZWJ, Pictographic) => !pict,
+        // flags are made of regional indicator pairs
+        (RegionalIndicator, RegionalIndicator) => ris.is_multiple_of(2),
+        _ => true,
+    }
+}
+
+/// an iterator over the extended grapheme clusters of a str
+/// made by the graphemes function
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    s: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.s.char_indices();
+        let (_, first) = chars.next()?;
+
+        let mut prev = class(first);
+        let mut pict = prev == Class::Pictographic;
+        let mut ris = (prev == Class::RegionalIndicator) as usize;
+        let mut end = self.s.len();
+        for (idx, c) in chars {
+            let next = class(c);
+            if breaks(prev, next, pict, ris) {
+                end = idx;
+                break;
+            }
+
+            pict = match next {
+                Class::Extend if prev != Class::ZWJ => pict,
+                Class::ZWJ => pict,
+                _ => next == Class::Pictographic,
+            };
+            ris = match next {
+                Class::RegionalIndicator => ris + 1,
+                _ => 0,
+            };
+            prev = next;
+        }
+
+        let (cluster, rest) = self.s.split_at(end);
+        self.s = rest;
+
+        Some(cluster)
+    }
+}
+
+/// splits the str into its extended grapheme clusters, as defined by UAX #29
+/// that is, what a user sees as one character,
+/// e.g., an 'e' with a combining acute accent, a flag or an emoji zwj sequence
+/// the indic conjunct rule is not supported
+///
+/// # Examples
+/// ```
+/// use bararaq::grapheme::graphemes;
+///
+/// let clusters = graphemes("e\u{301}🇯🇵!").collect::<Vec<&str>>();
+/// assert_eq!(clusters, vec!["e\u{301}", "🇯🇵", "!"]);
+/// ```
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { s }
+}
+
+/// a single extended grapheme cluster, stored inline so that it is Copy like a char
+/// used wherever bararaq stores a character cell, e.g., the Text value
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Grapheme {
+    len: u8,
+    bytes: [u8; CAPACITY],
+}
+
+impl Grapheme {
+    /// makes a grapheme out of the first cluster of the str, the rest of the str is ignored
+    /// a cluster longer than CAPACITY bytes is cut down to the chars that fit
+    pub fn new(s: &str) -> Self {
+        let cluster = graphemes(s).next().unwrap_or_default();
+        let len = cluster
+            .char_indices()
+            .map(|(idx, c)| idx + c.len_utf8())
+            .take_while(|end| *end <= CAPACITY)
+            .last()
+            .unwrap_or(0);
+
+        let mut bytes = [0; CAPACITY];
+        bytes[..len].copy_from_slice(&cluster.as_bytes()[..len]);
+
+        Self {
+            len: len as u8,
+            bytes,
+        }
+    }
+
+    /// the cluster as a str
+    pub fn as_str(&self) -> &str {
+        // only ever made out of whole chars
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+
+    /// the chars the cluster is made of
+    pub fn chars(&self) -> std::str::Chars<'_> {
+        self.as_str().chars()
+    }
+
+    /// the first char of the cluster, e.g., the 'e' of an 'e' with a combining accent
+    pub fn base(&self) -> char {
+        self.chars().next().unwrap_or_default()
+    }
+
+    /// whether the cluster is a single char
+    pub fn is_char(&self) -> bool {
+        self.chars().nth(1).is_none()
+    }
+
+    /// appends the char if it continues the cluster, e.g., a combining mark or a zwj
+    /// returns whether it did; a char that continues the cluster but does not fit is dropped
+    pub fn push(&mut self, c: char) -> bool {
+        let mut s = self.as_str().to_string();
+        s.push(c);
+        if graphemes(&s).nth(1).is_some() {
+            return false;
+        }
+
+        let len = s.len();
+        if len <= CAPACITY {
+            self.bytes[..len].copy_from_slice(s.as_bytes());
+            self.len = len as u8;
+        }
+
+        true
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(value: char) -> Self {
+        let mut bytes = [0; CAPACITY];
+        let len = value.encode_utf8(&mut bytes).len();
+
+        Self {
+            len: len as u8,
+            bytes,
+        }
+    }
+}
+
+impl std::fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::fmt::Debug for Grapheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Grapheme({:?})", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod grapheme {
+    use super::{graphemes, Grapheme, CAPACITY};
+
+    #[test]
+    fn clusters() {
+        [
+            ("abc", vec!["a", "b", "c"]),
+            ("\r\n\n", vec!["\r\n", "\n"]),
+            // combining marks
+            ("e\u{301}\u{323}x", vec!["e\u{301}\u{323}", "x"]),
+            ("مَرحبا", vec!["مَ", "ر", "ح", "ب", "ا"]),
+            // lam alef is a ligature of two clusters
+            ("لا", vec!["ل", "ا"]),
+            // hangul jamo and syllables
+            (
+                "\u{1100}\u{1161}\u{11a8}한",
+                vec!["\u{1100}\u{1161}\u{11a8}", "한"],
+            ),
+            // emoji with a skin tone, a zwj family and flags
+            ("👍🏽👨‍👩‍👧‍👦", vec!["👍🏽", "👨‍👩‍👧‍👦"]),
+            ("🇯🇵🇫🇷🇩", vec!["🇯🇵", "🇫🇷", "🇩"]),
+            // a zwj that does not join two emoji
+            ("a\u{200d}👍", vec!["a\u{200d}", "👍"]),
+        ]
+        .into_iter()
+        .for_each(|(s, clusters)| {
+            assert_eq!(graphemes(s).collect::<Vec<&str>>(), clusters, "{:?}", s);
+        });
+    }
+
+    #[test]
+    fn storage() {
+        let g = Grapheme::new("👨‍👩‍👧‍👦 and the rest");
+        assert_eq!(g.as_str(), "👨‍👩‍👧‍👦");
+        assert_eq!(g.base(), '👨');
+        assert!(!g.is_char());
+        assert_eq!(Grapheme::from('x'), Grapheme::new("x"));
+
+        let mut g = Grapheme::from('e');
+        assert!(g.push('\u{301}'));
+        assert!(!g.push('x'));
+        assert_eq!(g.as_str(), "e\u{301}");
+
+        // too long clusters keep the chars that fit
+        let long = format!("e{}", "\u{301}".repeat(20));
+        assert_eq!(Grapheme::new(&long).as_str().len(), CAPACITY);
+    }
+}
@@ -1,89 +1,412 @@
 /// keyboard raw input listening and decoding into human readable keyboard input events
 pub mod keyboard;
+/// kitty keyboard protocol, for unambiguous keys and key release events
+pub mod kitty;
 /// mouse/touchpad raw input listening and decoding into human readable keyboard input events
 pub mod mouse;
+/// streaming vt input parser, splits the input bytes into whole sequences
+pub mod parser;
+/// poll c ffi, waits on input file descriptors with a timeout
+pub mod poll;
 /// window user input events, such as a resize or a focus change
 pub mod window;
+/// xterm modifyOtherKeys, for modifier rich shortcuts
+pub mod xterm;
 
-use keyboard::{decode_ki, decode_ki_kai, Char, KbdEvent, PasteEvent};
+use keyboard::{decode_ki, Char, KbdEvent, KeyKind, Modifiers, PasteEvent, CC};
 use mouse::{decode_mi, MouseEvent};
+use parser::{Parser, Sequence};
 use window::WindowEvent;
 
-use std::io::BufRead;
-use std::io::StdinLock;
-use std::time::SystemTime;
+use std::io::{self, BufRead};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::components::Term;
-use crate::console::winsize::winsize;
+use crate::console::{
+    self,
+    clipboard::{self, ClipboardEvent},
+    signals::{self, SIGCONT, SIGTSTP, SIGWINCH},
+};
+use crate::grapheme::{graphemes, Grapheme};
+
+pub use poll::poll_fds;
 
 /// reads the keyboard input event bytes
-pub fn read<'a>(reader: &'a mut StdinLock, buffer: &'a mut Vec<u8>) -> &'a mut Vec<u8> {
-    // TODO: non blocking reads
+/// blocks until some input arrives, an empty buffer means that the reader hung up
+/// the reader is usually the stdin lock, but can be anything buffered, e.g., a pty::PtyReader
+///
+/// # Errors
+/// returns the reader's error, e.g., EIO once the terminal is gone
+/// a read that gets interrupted by a signal is retried
+pub fn read<'a>(
+    reader: &'a mut impl BufRead,
+    buffer: &'a mut Vec<u8>,
+) -> io::Result<&'a mut Vec<u8>> {
     buffer.clear();
 
-    let buf = reader.fill_buf().unwrap();
-    buffer.extend_from_slice(buf);
-
-    let n = buf.len();
+    let n = loop {
+        match reader.fill_buf() {
+            Ok(buf) => {
+                buffer.extend_from_slice(buf);
+                break buf.len();
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    };
     reader.consume(n);
 
-    buffer
+    Ok(buffer)
 }
 
-/// resolves the read input bytes to an InputEvent struct instance
-pub fn event(bytes: &[u8], ws: &mut winsize) -> InputEvent {
-    // FIXME: this only gets triggered on this blocking event fn
-    // it needs to be triggered on the actual resize event
-    if ws.resized() {
-        return InputEvent {
-            time: SystemTime::now(),
-            event: Interaction::WindowEvent(WindowEvent::WindowResized),
-        };
-    } else if bytes.len() % 6 == 0 && bytes[..3] == [27, 91, 77] {
-        // mouse
-        return InputEvent {
+/// reads the input event bytes, waiting at most for the given timeout
+/// returns None if no input arrived before the timeout ran out
+/// and an empty buffer if the reader hung up
+///
+/// # Errors
+/// returns an error if the reader can't be polled, see poll_fds, or can't be read, see read
+pub fn read_timeout<'a>(
+    reader: &'a mut (impl BufRead + AsRawFd),
+    buffer: &'a mut Vec<u8>,
+    timeout: Duration,
+) -> io::Result<Option<&'a mut Vec<u8>>> {
+    // NOTE: read always consumes everything fill_buf returns
+    // so the reader never holds bytes that poll can't see
+    if poll_fds(&[reader.as_raw_fd()], Some(timeout))?.is_empty() {
+        buffer.clear();
+
+        return Ok(None);
+    }
+
+    Ok(Some(read(reader, buffer)?))
+}
+
+/// reads the input event bytes if there are any, without waiting
+/// returns None if there was no input to read
+///
+/// # Errors
+/// the same as read_timeout
+pub fn try_read<'a>(
+    reader: &'a mut (impl BufRead + AsRawFd),
+    buffer: &'a mut Vec<u8>,
+) -> io::Result<Option<&'a mut Vec<u8>>> {
+    read_timeout(reader, buffer, Duration::ZERO)
+}
+
+/// waits for input or for a window resize, at most for the given timeout
+/// a timeout of None waits until something happens
+/// returns the resolved input events; a resize comes as a WindowResized event
+/// as soon as the SIGWINCH signal is received
+/// the decoder keeps what spans more than one call, e.g., a large bracketed paste
+/// or a lone esc, which comes as the esc key once the decoder's esc timeout runs out
+/// if the console session has job control on, ctrl-z suspends the program from here
+/// and a WindowResumed event is returned once it gets continued
+/// returns an empty vec if the timeout ran out first
+///
+/// # Errors
+/// returns an UnexpectedEof error once the reader hung up, e.g., the terminal was closed,
+/// no more events can come after that
+/// also returns an error if the reader can't be polled or read, see poll_fds and read
+pub fn poll_events(
+    reader: &mut (impl BufRead + AsRawFd),
+    decoder: &mut Decoder,
+    timeout: Option<Duration>,
+) -> io::Result<Vec<InputEvent>> {
+    let input = reader.as_raw_fd();
+    let mut fds = vec![input];
+    if let Some(fd) = window::listen_resize() {
+        fds.push(fd);
+    }
+
+    // a pending lone esc becomes the esc key once its timeout runs out
+    let timeout = match (timeout, decoder.esc_left()) {
+        (Some(timeout), Some(left)) => Some(timeout.min(left)),
+        (timeout, left) => timeout.or(left),
+    };
+    let ready = poll_fds(&fds, timeout)?;
+
+    let mut v = vec![];
+
+    // the signals pipe is drained even if poll got interrupted by the signal itself
+    // multiple resizes since the last call make only one event
+    let pending = signals::pending();
+    if pending.contains(&SIGWINCH) {
+        v.push(InputEvent {
             time: SystemTime::now(),
-            event: Interaction::MouseEvent(decode_mi(bytes).remove(0)),
-        };
-    } else if bytes.len() < 9 {
-        // BUG: 'لا' arabic char breaks the decode_ki function since it's 2 unicode chars combined char
-        // i could use decode_ki_kai and take the first char only, but that breaks the combined
-        // char
-        // TODO: implement unicode combined chars support
-        // keyboard
-        return InputEvent {
+            event: Interaction::WindowEvent(window::window_resized(input)),
+        });
+    }
+    // ctrl-z with the terminal signals on
+    if pending.contains(&SIGTSTP) {
+        console::suspend();
+    }
+    if pending.contains(&SIGCONT) {
+        console::session::continued();
+        v.push(InputEvent {
             time: SystemTime::now(),
-            event: Interaction::KbdEvent(decode_ki(bytes).unwrap()),
-            // event: Interaction::KbdEvent(decode_ki_kai(bytes.to_vec()).remove(0).unwrap()),
+            event: Interaction::WindowEvent(WindowEvent::WindowResumed),
+        });
+    }
+
+    if ready.contains(&input) {
+        let mut buffer = std::mem::take(&mut decoder.buffer);
+        let events = match read(reader, &mut buffer) {
+            // ready with nothing to read, the reader hung up
+            Ok(bytes) if bytes.is_empty() => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) => Ok(decoder.decode(&buffer)),
+            Err(e) => Err(e),
         };
-    } else {
-        // paste
-        return InputEvent {
-            event: Interaction::PasteEvent(PasteEvent(
-                decode_ki_kai(bytes.to_vec())
-                    .into_iter()
-                    .filter(|r| r.is_ok())
-                    .map(|r| r.unwrap())
-                    .filter(|c| c.is_char())
-                    .map(|c| {
-                        let Char::Char(ch) = c.char else {
-                            unreachable!()
-                        };
-                        ch
-                    })
-                    .collect::<String>(),
-            )),
+        decoder.buffer = buffer;
+
+        for ie in events? {
+            // ctrl-z in raw mode, which never raises SIGTSTP
+            // its repeats and release, from the kitty keyboard protocol, must not suspend again
+            if let Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('z'),
+                modifiers: Modifiers(2),
+                kind: KeyKind::Press,
+                ..
+            }) = ie.event
+            {
+                if console::session::job_control() {
+                    console::suspend();
+
+                    continue;
+                }
+            }
+            v.push(ie);
+        }
+    }
+    v.extend(decoder.expire());
+
+    Ok(v)
+}
+
+// the focus reports, from \x1b[?1004h
+const FOCUS_GAINED: &[u8] = b"\x1b[I";
+const FOCUS_LOST: &[u8] = b"\x1b[O";
+// the clipboard replies, from console::clipboard::query
+const CLIPBOARD: &[u8] = b"52;";
+
+// how long a lone esc waits for the rest of a sequence by default
+const ESC_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// turns the read input bytes into input events
+/// keeps the state that spans more than one read,
+/// e.g., an escape sequence split between two reads or a bracketed paste
+/// that is larger than the read buffer
+#[derive(Debug)]
+pub struct Decoder {
+    // the read buffer
+    buffer: Vec<u8>,
+    parser: Parser,
+    esc_timeout: Duration,
+    // when the pending lone esc was read, if there is one
+    esc_since: Option<Instant>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self {
+            buffer: vec![],
+            parser: Parser::new(),
+            esc_timeout: ESC_TIMEOUT,
+            esc_since: None,
+        }
+    }
+}
+
+impl Decoder {
+    /// creates a new decoder, with an esc timeout of 50ms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets how long a lone esc waits for more bytes before it becomes the esc key
+    /// the same goes for an esc followed by only an introducer, e.g., \x1b[,
+    /// which becomes that key with alt
+    /// an esc followed by a char within the timeout is that char with alt,
+    /// and an esc followed by the rest of an escape sequence is that sequence's key,
+    /// even over a slow link that splits the sequence
+    /// a zero timeout makes an esc at the end of a read the esc key right away
+    pub fn esc_timeout(mut self, timeout: Duration) -> Self {
+        self.esc_timeout = timeout;
+
+        self
+    }
+
+    /// whether the decoder is in the middle of a bracketed paste
+    pub fn is_pasting(&self) -> bool {
+        self.parser.is_pasting()
+    }
+
+    /// the time left before the pending lone esc becomes the esc key
+    /// returns None if there is no pending esc
+    pub fn esc_left(&self) -> Option<Duration> {
+        self.esc_since
+            .map(|since| self.esc_timeout.saturating_sub(since.elapsed()))
+    }
+
+    /// returns the esc key event if the pending lone esc has waited for the whole timeout,
+    /// or the alt key of the introducer that followed it, e.g., alt + [
+    /// poll_events calls this, it only needs calling when decoding by hand
+    pub fn expire(&mut self) -> Option<InputEvent> {
+        if self.esc_left()? > Duration::ZERO {
+            return None;
+        }
+        self.esc_since = None;
+
+        Some(InputEvent {
+            event: interaction(self.parser.flush_esc()?),
             time: SystemTime::now(),
+        })
+    }
+
+    /// resolves the read input bytes to input events, in the order they came in
+    /// a sequence that is not complete yet is kept until the next call,
+    /// a lone esc at the end is kept until the esc timeout runs out, see expire
+    /// focus reports come as WindowGainedFocus and WindowLostFocus events
+    /// a bracketed paste comes as a single PasteEvent carrying the exact pasted text,
+    /// newlines and tabs included, once its end delimiter is read;
+    /// until then the pasted bytes are kept and no event is returned for them
+    /// the same goes for the reply to a clipboard query, which comes as a ClipboardEvent
+    /// a sequence that no decoder knows of comes as an Unknown event with its bytes
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<InputEvent> {
+        let mut seqs = self.parser.parse(bytes);
+        if self.esc_timeout.is_zero() {
+            seqs.extend(self.parser.flush_esc());
+        }
+        // an esc can only be pending if it is the last byte read
+        self.esc_since = match self.parser.is_esc() {
+            true if !bytes.is_empty() => Some(Instant::now()),
+            true => self.esc_since,
+            false => None,
         };
+
+        clusters(seqs)
+            .into_iter()
+            .map(interaction)
+            .map(|event| InputEvent {
+                event,
+                time: SystemTime::now(),
+            })
+            .collect()
     }
 }
 
+// joins the chars of a read that make a single grapheme cluster, e.g., an emoji zwj sequence
+// control chars are keys of their own, so "\r\n" stays enter + ctrl-j
+// chars that only look like utf8, e.g., a surrogate, are given back as invalid
+fn clusters(seqs: Vec<Sequence>) -> Vec<Sequence> {
+    let mut v = vec![];
+    let mut text = String::new();
+    let flush = |v: &mut Vec<Sequence>, text: &mut String| {
+        v.extend(graphemes(text).map(|g| Sequence::Char(g.as_bytes().to_vec())));
+        text.clear();
+    };
+
+    for seq in seqs {
+        match seq {
+            Sequence::Char(bytes) if !matches!(bytes[..], [0..=31 | 127]) => {
+                match String::from_utf8(bytes) {
+                    Ok(s) => text.push_str(&s),
+                    Err(e) => {
+                        flush(&mut v, &mut text);
+                        v.push(Sequence::Invalid(e.into_bytes()));
+                    }
+                }
+            }
+            seq => {
+                flush(&mut v, &mut text);
+                v.push(seq);
+            }
+        }
+    }
+    flush(&mut v, &mut text);
+
+    v
+}
+
+// resolves a whole input sequence
+// the sequences that are not known come as Unknown, with their bytes
+fn interaction(seq: Sequence) -> Interaction {
+    resolve(&seq).unwrap_or_else(|| Interaction::Unknown(seq.into_bytes()))
+}
+
+// returns None for the sequences that are not known
+fn resolve(seq: &Sequence) -> Option<Interaction> {
+    let ke = match seq {
+        Sequence::Char(bytes) => match std::str::from_utf8(bytes) {
+            // more than one char
+            Ok(s) if s.chars().nth(1).is_some() => KbdEvent {
+                char: Char::Grapheme(Grapheme::new(s)),
+                ..Default::default()
+            },
+            _ => decode_ki(bytes).ok()?,
+        },
+        Sequence::Alt(bytes) => {
+            let mut ke = decode_ki(&bytes[1..]).ok()?;
+            // alt
+            ke.modifiers = Modifiers(ke.modifiers.0 | 4);
+
+            ke
+        }
+        Sequence::Esc => KbdEvent::default(),
+        Sequence::Csi(seq) => match &seq[..] {
+            FOCUS_GAINED => return Some(Interaction::WindowEvent(WindowEvent::WindowGainedFocus)),
+            FOCUS_LOST => return Some(Interaction::WindowEvent(WindowEvent::WindowLostFocus)),
+            // the kitty keyboard protocol extends the legacy encoding,
+            // so its decoder takes the legacy sequences too
+            _ => xterm::decode(seq).or_else(|| kitty::decode(seq))?,
+        },
+        Sequence::Ss3(seq) => KbdEvent {
+            char: Char::CC(CC::from_ss3(*seq.get(2)?)?),
+            ..Default::default()
+        },
+        Sequence::Mouse(seq) => {
+            return decode_mi(seq)
+                .ok()?
+                .into_iter()
+                .next()
+                .map(Interaction::MouseEvent)
+        }
+        Sequence::Osc(body) => {
+            return clipboard::decode_reply(body.strip_prefix(CLIPBOARD)?)
+                .map(Interaction::ClipboardEvent)
+        }
+        Sequence::Paste(text) => {
+            return Some(Interaction::PasteEvent(PasteEvent(
+                String::from_utf8_lossy(text).into_owned(),
+            )))
+        }
+        Sequence::Dcs(_) | Sequence::Invalid(_) => return None,
+    };
+
+    Some(Interaction::KbdEvent(ke))
+}
+
+/// errors for input decoding
+#[derive(Debug, PartialEq)]
+pub enum InputError {
+    /// the bytes are not a sequence that the decoder knows of
+    UnknownSequence(Vec<u8>),
+    /// the bytes are neither valid utf8 nor an escape sequence
+    InvalidUtf8(Vec<u8>),
+    /// the bytes end before the sequence they start does
+    Incomplete(Vec<u8>),
+}
+
 #[derive(Debug)]
 pub enum Interaction {
     WindowEvent(WindowEvent),
     KbdEvent(KbdEvent),
     MouseEvent(MouseEvent),
     PasteEvent(PasteEvent),
+    /// the reply to a console::clipboard::query
+    ClipboardEvent(ClipboardEvent),
+    /// a sequence that no decoder knows of, with its raw bytes
+    /// e.g., a key from a newer protocol or a reply to a query that this crate does not make
+    Unknown(Vec<u8>),
 }
 
 #[derive(Debug)]
@@ -91,3 +414,397 @@ pub struct InputEvent {
     pub event: Interaction,
     pub time: SystemTime,
 }
+
+#[cfg(test)]
+mod decoder {
+    use super::keyboard::{Char, KbdEvent, Modifiers, PasteEvent, CC};
+    use super::window::WindowEvent;
+    use super::{Decoder, InputError, InputEvent, Interaction};
+    use crate::console::clipboard::ClipboardEvent;
+    use std::time::Duration;
+
+    fn paste(decoder: &mut Decoder, bytes: &[u8]) -> Vec<String> {
+        decoder
+            .decode(bytes)
+            .into_iter()
+            .filter_map(|ie| match ie.event {
+                Interaction::PasteEvent(PasteEvent(s)) => Some(s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_text() {
+        let mut decoder = Decoder::new();
+        let text = paste(
+            &mut decoder,
+            b"\x1b[200~fn main() {\n\tprint!(\"hi\");\r\n}\x1b[201~",
+        );
+
+        assert_eq!(text, vec!["fn main() {\n\tprint!(\"hi\");\r\n}"]);
+        assert!(!decoder.is_pasting());
+    }
+
+    #[test]
+    fn split_reads() {
+        let mut decoder = Decoder::new();
+        let mut bytes = b"\x1b[200~".to_vec();
+        bytes.extend("طويل ".repeat(2000).as_bytes());
+        bytes.extend(b"\x1b[201~");
+
+        let mut pasted = vec![];
+        // the end delimiter gets split between reads too
+        for chunk in bytes.chunks(1021) {
+            pasted.extend(paste(&mut decoder, chunk));
+        }
+
+        assert_eq!(pasted, vec!["طويل ".repeat(2000)]);
+    }
+
+    #[test]
+    fn keys_around_paste() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode(b"a\x1b[200~pasted\x1b[201~b");
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('a'),
+                ..
+            })
+        ));
+        assert!(
+            matches!(&events[1].event, Interaction::PasteEvent(PasteEvent(s)) if s == "pasted")
+        );
+        assert!(matches!(
+            events[2].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('b'),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn focus() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode(b"\x1b[Ox\x1b[I");
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0].event,
+            Interaction::WindowEvent(WindowEvent::WindowLostFocus)
+        ));
+        assert!(matches!(events[1].event, Interaction::KbdEvent(_)));
+        assert!(matches!(
+            events[2].event,
+            Interaction::WindowEvent(WindowEvent::WindowGainedFocus)
+        ));
+    }
+
+    #[test]
+    fn clipboard_reply() {
+        let mut decoder = Decoder::new();
+        // the reply may end with ST and be split between reads
+        let mut events = decoder.decode(b"\x1b]52;c;YmFy");
+        assert!(events.is_empty());
+        events.extend(decoder.decode(b"YXJhcQ==\x1b"));
+        events.extend(decoder.decode(b"\\"));
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0].event,
+            Interaction::ClipboardEvent(ClipboardEvent { text, .. }) if text == "bararaq"
+        ));
+    }
+
+    #[test]
+    fn mixed_read() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode(b"\x1b[Ab\x1b[M !!\x1b[1;5");
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[2].event, Interaction::MouseEvent(_)));
+
+        // the rest of the split sequence
+        let mut decoder = decoder.esc_timeout(Duration::ZERO);
+        let events = decoder.decode(b"C\x1b");
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::CC(CC::Right),
+                modifiers: Modifiers(2),
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[1].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::CC(CC::ESC),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn clusters() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode("e\u{301}👨‍👩‍👧🇯🇵لا\r\n".as_bytes());
+
+        let chars = events
+            .into_iter()
+            .map(|ie| match ie.event {
+                Interaction::KbdEvent(KbdEvent {
+                    char: Char::Grapheme(g),
+                    ..
+                }) => g.to_string(),
+                Interaction::KbdEvent(KbdEvent {
+                    char: Char::Char(c),
+                    ..
+                }) => c.to_string(),
+                Interaction::KbdEvent(KbdEvent {
+                    char: Char::CC(cc), ..
+                }) => format!("{:?}", cc),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<String>>();
+
+        assert_eq!(chars, vec!["e\u{301}", "👨‍👩‍👧", "🇯🇵", "ل", "ا", "CR", "j"]);
+    }
+
+    #[test]
+    fn esc_timeout() {
+        let mut decoder = Decoder::new().esc_timeout(Duration::from_millis(10));
+
+        // an esc split from the rest of its sequence
+        assert!(decoder.decode(b"\x1b").is_empty());
+        assert!(decoder.expire().is_none());
+        let events = decoder.decode(b"[A");
+        assert!(matches!(
+            events[..],
+            [InputEvent {
+                event: Interaction::KbdEvent(KbdEvent {
+                    char: Char::CC(CC::Up),
+                    ..
+                }),
+                ..
+            }]
+        ));
+
+        // alt + x
+        decoder.decode(b"\x1b");
+        let events = decoder.decode(b"x");
+        assert!(matches!(
+            events[0].event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('x'),
+                modifiers: Modifiers(4),
+                ..
+            })
+        ));
+
+        // a lone esc
+        assert!(decoder.decode(b"\x1b").is_empty());
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(matches!(
+            decoder.expire(),
+            Some(InputEvent {
+                event: Interaction::KbdEvent(KbdEvent {
+                    char: Char::CC(CC::ESC),
+                    ..
+                }),
+                ..
+            })
+        ));
+        assert_eq!(decoder.esc_left(), None);
+    }
+
+    #[test]
+    fn alt_introducers() {
+        let mut decoder = Decoder::new().esc_timeout(Duration::from_millis(10));
+
+        // alt + ] starts an osc string, which a keyboard never ends,
+        // alt + [ and alt + O start a csi and an ss3 sequence
+        for (alt, key) in [(&b"\x1b]"[..], ']'), (b"\x1b[", '['), (b"\x1bO", 'O')] {
+            assert!(decoder.decode(alt).is_empty(), "{:?}", alt);
+            std::thread::sleep(Duration::from_millis(10));
+            assert!(matches!(
+                decoder.expire(),
+                Some(InputEvent {
+                    event: Interaction::KbdEvent(KbdEvent {
+                        char: Char::Char(c),
+                        modifiers: Modifiers(4),
+                        ..
+                    }),
+                    ..
+                }) if c == key
+            ));
+
+            let events = decoder.decode(b"a");
+            assert_eq!(events.len(), 1, "{:?}", alt);
+            assert!(matches!(
+                events[0].event,
+                Interaction::KbdEvent(KbdEvent {
+                    char: Char::Char('a'),
+                    modifiers: Modifiers(0),
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn keys() {
+        let mut decoder = Decoder::new().esc_timeout(Duration::ZERO);
+        [
+            (&b"\x7f"[..], CC::BS, 0),
+            (b"\x08", CC::BS, 0),
+            (b"\t", CC::TAB, 0),
+            (b"\x1b[Z", CC::TAB, 8),
+            (b"\r", CC::CR, 0),
+            (b"\x1b", CC::ESC, 0),
+            (b"\x1b[A", CC::Up, 0),
+            (b"\x1bOB", CC::Down, 0),
+            (b"\x1b[1;3C", CC::Right, 4),
+            (b"\x1b[1;9D", CC::Left, 1),
+            (b"\x1b[2~", CC::Insert, 0),
+            (b"\x1b[3~", CC::Delete, 0),
+            (b"\x1b[3;5~", CC::Delete, 2),
+            (b"\x1b[H", CC::Home, 0),
+            (b"\x1bOH", CC::Home, 0),
+            (b"\x1b[1~", CC::Home, 0),
+            (b"\x1b[1;5H", CC::Home, 2),
+            (b"\x1b[F", CC::End, 0),
+            (b"\x1b[4~", CC::End, 0),
+            (b"\x1b[1;2F", CC::End, 8),
+            (b"\x1b[5~", CC::PageUp, 0),
+            (b"\x1b[6;3~", CC::PageDown, 4),
+            (b"\x1bOP", CC::F1, 0),
+            (b"\x1b[1;5Q", CC::F2, 2),
+            (b"\x1bOR", CC::F3, 0),
+            (b"\x1b[14~", CC::F4, 0),
+            (b"\x1b[15~", CC::F5, 0),
+            (b"\x1b[24;2~", CC::F12, 8),
+            (b"\x1b[25~", CC::F13, 0),
+            (b"\x1b[34~", CC::F20, 0),
+            (b"\x1b[57387u", CC::F24, 0),
+            (b"\x1bOp", CC::Kp0, 0),
+            (b"\x1bOy", CC::Kp9, 0),
+            (b"\x1bOn", CC::KpDecimal, 0),
+            (b"\x1bOo", CC::KpDivide, 0),
+            (b"\x1bOj", CC::KpMultiply, 0),
+            (b"\x1bOm", CC::KpSubtract, 0),
+            (b"\x1bOk", CC::KpAdd, 0),
+            (b"\x1bOM", CC::KpEnter, 0),
+            (b"\x1bOX", CC::KpEqual, 0),
+            (b"\x1bOl", CC::KpSeparator, 0),
+            (b"\x1bOE", CC::KpBegin, 0),
+            (b"\x1b[57414;5u", CC::KpEnter, 2),
+            (b"\x1b[57428u", CC::MediaPlay, 0),
+            (b"\x1b[57430u", CC::MediaPlayPause, 0),
+            (b"\x1b[57435u", CC::MediaTrackNext, 0),
+            (b"\x1b[57439u", CC::RaiseVolume, 0),
+            (b"\x1b[57440u", CC::MuteVolume, 0),
+        ]
+        .into_iter()
+        .for_each(|(seq, cc, modifiers)| {
+            let events = decoder.decode(seq);
+            assert_eq!(events.len(), 1, "{:?}", seq);
+            let Interaction::KbdEvent(ke) = &events[0].event else {
+                panic!("{:?} is not a key", seq);
+            };
+            assert_eq!(ke.char, Char::CC(cc), "{:?}", seq);
+            assert_eq!(ke.modifiers, Modifiers(modifiers), "{:?}", seq);
+        });
+    }
+
+    #[test]
+    fn fast_typing() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode(b"typed fast");
+
+        // one event per key, not a paste
+        assert_eq!(events.len(), 10);
+        assert!(events
+            .iter()
+            .all(|ie| matches!(ie.event, Interaction::KbdEvent(_))));
+    }
+
+    #[test]
+    fn unknown() {
+        let mut decoder = Decoder::new();
+        let events = decoder.decode(
+            b"\x1b[99;99X\x1bP>|xterm\x1b\\\x1b[M\x10!!\x1bOz\x1b[12;5R\xff\xe2\x82\xed\xa0\x80a",
+        );
+
+        let unknown = events
+            .iter()
+            .filter_map(|ie| match &ie.event {
+                Interaction::Unknown(bytes) => Some(&bytes[..]),
+                _ => None,
+            })
+            .collect::<Vec<&[u8]>>();
+        assert_eq!(
+            unknown,
+            vec![
+                &b"\x1b[99;99X"[..],
+                b"\x1bP>|xterm\x1b\\",
+                b"\x1b[M\x10!!",
+                b"\x1bOz",
+                b"\x1b[12;5R",
+                b"\xff",
+                b"\xe2\x82",
+                b"\xed\xa0\x80",
+            ]
+        );
+        // the decoding goes on after them
+        assert!(matches!(
+            events.last().unwrap().event,
+            Interaction::KbdEvent(KbdEvent {
+                char: Char::Char('a'),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn no_panics() {
+        use super::keyboard::{decode_ki, decode_ki_kai};
+        use super::mouse::decode_mi;
+
+        assert_eq!(
+            decode_ki(b"\x1b[9;9~").unwrap_err(),
+            InputError::UnknownSequence(b"\x1b[9;9~".to_vec())
+        );
+        // a surrogate, which looks like a 3 bytes utf8 char
+        assert_eq!(
+            decode_ki(b"\xed\xa0\x80").unwrap_err(),
+            InputError::InvalidUtf8(b"\xed\xa0\x80".to_vec())
+        );
+        assert_eq!(format!("{:?}", Modifiers(200)), "INVALID");
+
+        // every byte after the start of every kind of sequence
+        let starts: [&[u8]; 7] = [
+            b"",
+            b"\x1b",
+            b"\x1b[",
+            b"\x1bO",
+            b"\x1b[1;",
+            b"\x1b[15;",
+            b"\x1b[M",
+        ];
+        for start in starts {
+            for b in 0..=255 {
+                let mut bytes = start.to_vec();
+                bytes.push(b);
+                _ = decode_ki(&bytes);
+                _ = decode_ki_kai(bytes.clone());
+                bytes.extend(b"!!");
+                _ = decode_mi(&bytes);
+                _ = Decoder::new().decode(&bytes);
+            }
+        }
+    }
+}
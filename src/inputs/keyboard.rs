@@ -1,21 +1,24 @@
-use std::collections::VecDeque;
-use std::io::BufRead;
-use std::io::Error;
 use std::io::Read;
-use std::io::StdinLock;
 use std::io::Write;
 
+use crate::grapheme::Grapheme;
+use crate::inputs::InputError;
+
 /// char enum
-/// could be either a control character (cc) or a normal utf-8 char
+/// could be either a control character (cc), a normal utf-8 char or a grapheme cluster
 #[derive(Debug, PartialEq)]
 pub enum Char {
     /// control character such as \r (carriage return)
     CC(CC),
     /// utf-8 character
     Char(char),
+    /// a grapheme cluster made of more than one char, e.g., an emoji zwj sequence,
+    /// a flag or a letter with combining marks
+    Grapheme(Grapheme),
 }
 
-/// paste event for when ctrl+v is hit
+/// paste event for when text is pasted into the terminal
+/// requires bracketed paste, carries the exact pasted text, newlines and tabs included
 #[derive(Debug)]
 pub struct PasteEvent(pub String);
 
@@ -30,106 +33,75 @@ impl Char {
         Char::Char(byte as char)
     }
 
-    fn from_cc(byte: u8) -> Self {
-        Char::CC(match byte {
+    fn from_cc(byte: u8) -> Option<Self> {
+        Some(Char::CC(match byte {
             9 => CC::TAB,
             13 => CC::CR,
             27 => CC::ESC,
-            127 => CC::BS,
-            val => unreachable!("unexpected byte value: '{}'. not a control char", val),
-        })
+            // some terminals send ^H for backspace, which makes ctrl + h indistinguishable from it
+            8 | 127 => CC::BS,
+            _ => return None,
+        }))
     }
 
-    fn from_ctrl_ascii(byte: u8) -> Self {
-        Char::Char(match byte {
+    fn from_ctrl_ascii(byte: u8) -> Option<Self> {
+        Some(Char::Char(match byte {
             0 => '@',
             1..=26 => (byte + 96) as char,
             27..30 => (byte + 64) as char,
             30 => (byte + 24) as char,
             31 => '_',
-            val => unreachable!("expected byte between 0 and 31, got '{}' instead", val),
-        })
+            _ => return None,
+        }))
     }
 
-    /// can't fail
-    fn from_utf8(bytes: &[u8]) -> Self {
-        Char::Char(std::str::from_utf8(bytes).unwrap().chars().last().unwrap())
+    /// returns None if the bytes are not valid utf8
+    /// the bytes of more than one char make a Grapheme, out of their first cluster
+    fn from_utf8(bytes: &[u8]) -> Option<Self> {
+        let s = std::str::from_utf8(bytes).ok()?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(Char::Char(c)),
+            (Some(_), Some(_)) => Some(Char::Grapheme(Grapheme::new(s))),
+            _ => None,
+        }
     }
 
-    fn from_arrow_key(byte: u8) -> Self {
-        Char::CC(match byte {
+    fn from_arrow_key(byte: u8) -> Option<Self> {
+        Some(Char::CC(match byte {
             65 => CC::Up,
             66 => CC::Down,
             67 => CC::Right,
             68 => CC::Left,
-            _ => unreachable!("already asserted this byte to be between 65 and 68"),
-        })
+            _ => return None,
+        }))
     }
 
     // fn keys that generate 3 bytes long input
-    fn from_fn_key3(byte: u8) -> Self {
-        Char::CC(match byte {
+    fn from_fn_key3(byte: u8) -> Option<Self> {
+        Some(Char::CC(match byte {
             80 => CC::F1,
             81 => CC::F2,
             82 => CC::F3,
             83 => CC::F4,
-            _ => unreachable!("already asserted this byte to be between 80 and 83"),
-        })
+            _ => return None,
+        }))
     }
 
-    fn from_cc_extra(byte: u8) -> Self {
-        Char::CC(match byte {
-            51 => CC::Insert,
-            52 => CC::End,
-            54 => CC::PageDown,
-            53 => CC::PageUp,
-            49 => CC::Home,
-            _ => unreachable!("already asserted this byte to be between 80 and 83"),
-        })
+    fn from_cc_extra(byte: u8) -> Option<Self> {
+        CC::from_csi(b'~', digit(byte)?).map(Char::CC)
     }
 
-    fn from_fn_key5(byte2: u8, byte3: u8) -> Self {
-        Char::CC(match byte3 {
-            53 if byte2 == 49 => CC::F5,
-            55 if byte2 == 49 => CC::F6,
-            56 if byte2 == 49 => CC::F7,
-            57 if byte2 == 49 => CC::F8,
-            48 if byte2 == 50 => CC::F9,
-            49 if byte2 == 50 => CC::F10,
-            51 if byte2 == 50 => CC::F11,
-            52 if byte2 == 50 => CC::F12,
-            _ => unreachable!(
-                "already asserted these bytes, 
-                if you really do reach here, it's because
-                i let some cases get away in my assertions\r\n
-                Please open an issue here \"https://github.com/uraneko/bararaq\" 
-                so that i can fix the bug"
-            ),
-        })
+    fn from_fn_key5(byte2: u8, byte3: u8) -> Option<Self> {
+        CC::from_csi(b'~', digit(byte2)? * 10 + digit(byte3)?).map(Char::CC)
     }
 
     // fn from_6b_with_mods(bytes: &[u8]) -> Self {}
+}
 
-    fn from_utf81(bytes: &[u8]) -> Self {
-        assert_eq!(bytes.len(), 1);
-
-        Self::from_utf8(bytes)
-    }
-    fn from_utf82(bytes: &[u8]) -> Self {
-        assert_eq!(bytes.len(), 2);
-
-        Self::from_utf8(bytes)
-    }
-    fn from_utf83(bytes: &[u8]) -> Self {
-        assert_eq!(bytes.len(), 3);
-
-        Self::from_utf8(bytes)
-    }
-    fn from_utf84(bytes: &[u8]) -> Self {
-        assert_eq!(bytes.len(), 4);
-
-        Self::from_utf8(bytes)
-    }
+// the value of an ascii digit byte
+fn digit(byte: u8) -> Option<u32> {
+    (byte as char).to_digit(10)
 }
 
 #[derive(Debug, PartialEq)]
@@ -155,7 +127,9 @@ pub enum CC {
     /// arrow left key
     Left,
     /// insert key
-    Insert, // Del
+    Insert,
+    /// delete key
+    Delete,
     /// home key
     Home,
     /// end key
@@ -193,6 +167,240 @@ pub enum CC {
     F11,
     /// f12 key
     F12,
+    /// f13 to f24 only come from terminals that send them as keys of their own,
+    /// e.g., rxvt or the linux console up to f20, and the kitty keyboard protocol
+    /// f13 key
+    F13,
+    /// f14 key
+    F14,
+    /// f15 key
+    F15,
+    /// f16 key
+    F16,
+    /// f17 key
+    F17,
+    /// f18 key
+    F18,
+    /// f19 key
+    F19,
+    /// f20 key
+    F20,
+    /// f21 key
+    F21,
+    /// f22 key
+    F22,
+    /// f23 key
+    F23,
+    /// f24 key
+    F24,
+    // keypad keys, in application keypad mode or with the kitty keyboard protocol
+    // the keypad arrows, home, end, insert, delete and page keys come as the regular ones
+    /// keypad 0 key
+    Kp0,
+    /// keypad 1 key
+    Kp1,
+    /// keypad 2 key
+    Kp2,
+    /// keypad 3 key
+    Kp3,
+    /// keypad 4 key
+    Kp4,
+    /// keypad 5 key
+    Kp5,
+    /// keypad 6 key
+    Kp6,
+    /// keypad 7 key
+    Kp7,
+    /// keypad 8 key
+    Kp8,
+    /// keypad 9 key
+    Kp9,
+    /// keypad . key
+    KpDecimal,
+    /// keypad / key
+    KpDivide,
+    /// keypad * key
+    KpMultiply,
+    /// keypad - key
+    KpSubtract,
+    /// keypad + key
+    KpAdd,
+    /// keypad enter key
+    KpEnter,
+    /// keypad = key
+    KpEqual,
+    /// keypad , key
+    KpSeparator,
+    /// keypad 5 key with num lock off
+    KpBegin,
+    // media keys, only reported by the kitty keyboard protocol
+    /// media play key
+    MediaPlay,
+    /// media pause key
+    MediaPause,
+    /// media play/pause key
+    MediaPlayPause,
+    /// media reverse key
+    MediaReverse,
+    /// media stop key
+    MediaStop,
+    /// media fast forward key
+    MediaFastForward,
+    /// media rewind key
+    MediaRewind,
+    /// media next track key
+    MediaTrackNext,
+    /// media previous track key
+    MediaTrackPrevious,
+    /// media record key
+    MediaRecord,
+    /// volume down key
+    LowerVolume,
+    /// volume up key
+    RaiseVolume,
+    /// volume mute key
+    MuteVolume,
+}
+
+impl CC {
+    /// the key of a CSI sequence, out of its final byte and its first parameter
+    /// e.g., the A of \x1b[1;5A, or the ~ and the 3 of \x1b[3~
+    pub(crate) fn from_csi(fin: u8, code: u32) -> Option<Self> {
+        Some(match fin {
+            b'A' => CC::Up,
+            b'B' => CC::Down,
+            b'C' => CC::Right,
+            b'D' => CC::Left,
+            b'E' => CC::KpBegin,
+            b'H' => CC::Home,
+            b'F' => CC::End,
+            b'P' => CC::F1,
+            b'Q' => CC::F2,
+            // any other parameter makes it a cursor position report, e.g., \x1b[12;5R
+            b'R' if code == 1 => CC::F3,
+            b'S' => CC::F4,
+            // shift + tab
+            b'Z' => CC::TAB,
+            b'~' => match code {
+                1 | 7 => CC::Home,
+                2 => CC::Insert,
+                3 => CC::Delete,
+                4 | 8 => CC::End,
+                5 => CC::PageUp,
+                6 => CC::PageDown,
+                11 => CC::F1,
+                12 => CC::F2,
+                13 => CC::F3,
+                14 => CC::F4,
+                15 => CC::F5,
+                17 => CC::F6,
+                18 => CC::F7,
+                19 => CC::F8,
+                20 => CC::F9,
+                21 => CC::F10,
+                23 => CC::F11,
+                24 => CC::F12,
+                25 => CC::F13,
+                26 => CC::F14,
+                28 => CC::F15,
+                29 => CC::F16,
+                31 => CC::F17,
+                32 => CC::F18,
+                33 => CC::F19,
+                34 => CC::F20,
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    /// the key of an SS3 sequence, out of its last byte
+    /// e.g., the P of \x1bOP, application cursor and keypad keys included
+    pub(crate) fn from_ss3(byte: u8) -> Option<Self> {
+        Some(match byte {
+            b'p'..=b'y' => [
+                CC::Kp0,
+                CC::Kp1,
+                CC::Kp2,
+                CC::Kp3,
+                CC::Kp4,
+                CC::Kp5,
+                CC::Kp6,
+                CC::Kp7,
+                CC::Kp8,
+                CC::Kp9,
+            ]
+            .into_iter()
+            .nth((byte - b'p') as usize)?,
+            b'j' => CC::KpMultiply,
+            b'k' => CC::KpAdd,
+            b'l' => CC::KpSeparator,
+            b'm' => CC::KpSubtract,
+            b'n' => CC::KpDecimal,
+            b'o' => CC::KpDivide,
+            b'M' => CC::KpEnter,
+            b'X' => CC::KpEqual,
+            b'A'..=b'F' | b'H' | b'P'..=b'S' => Self::from_csi(byte, 1)?,
+            _ => return None,
+        })
+    }
+
+    /// the functional keys the kitty keyboard protocol sends as private use area codepoints
+    pub(crate) fn from_kitty(code: u32) -> Option<Self> {
+        Some(match code {
+            57376..=57387 => [
+                CC::F13,
+                CC::F14,
+                CC::F15,
+                CC::F16,
+                CC::F17,
+                CC::F18,
+                CC::F19,
+                CC::F20,
+                CC::F21,
+                CC::F22,
+                CC::F23,
+                CC::F24,
+            ]
+            .into_iter()
+            .nth((code - 57376) as usize)?,
+            // the keypad digits, in the same order as their SS3 bytes
+            57399..=57408 => Self::from_ss3((code - 57399) as u8 + b'p')?,
+            57409 => CC::KpDecimal,
+            57410 => CC::KpDivide,
+            57411 => CC::KpMultiply,
+            57412 => CC::KpSubtract,
+            57413 => CC::KpAdd,
+            57414 => CC::KpEnter,
+            57415 => CC::KpEqual,
+            57416 => CC::KpSeparator,
+            57417 => CC::Left,
+            57418 => CC::Right,
+            57419 => CC::Up,
+            57420 => CC::Down,
+            57421 => CC::PageUp,
+            57422 => CC::PageDown,
+            57423 => CC::Home,
+            57424 => CC::End,
+            57425 => CC::Insert,
+            57426 => CC::Delete,
+            57427 => CC::KpBegin,
+            57428 => CC::MediaPlay,
+            57429 => CC::MediaPause,
+            57430 => CC::MediaPlayPause,
+            57431 => CC::MediaReverse,
+            57432 => CC::MediaStop,
+            57433 => CC::MediaFastForward,
+            57434 => CC::MediaRewind,
+            57435 => CC::MediaTrackNext,
+            57436 => CC::MediaTrackPrevious,
+            57437 => CC::MediaRecord,
+            57438 => CC::LowerVolume,
+            57439 => CC::RaiseVolume,
+            57440 => CC::MuteVolume,
+            _ => return None,
+        })
+    }
 }
 
 /// the keyboard event modifiers struct
@@ -215,14 +423,13 @@ impl std::fmt::Debug for Modifiers {
 const SUPER: u8 = 0x01;
 const CONTROL: u8 = 0x02;
 const ALT: u8 = 0x04;
-const SHIFT: u8 = 0x08;
+pub(crate) const SHIFT: u8 = 0x08;
 const NONE: u8 = 0x0;
 
 impl Modifiers {
+    // only the 4 lowest bits are modifiers
     fn from_byte(byte: u8) -> Self {
-        assert!(byte < 16);
-
-        Self(byte)
+        Self(byte & 0x0f)
     }
 
     fn as_text(&self) -> &str {
@@ -243,32 +450,20 @@ impl Modifiers {
             13 => "SUPER_SHIFT_ALT",
             14 => "CONTROL_SHIFT_ALT",
             15 => "CONTROL_SHIFT_ALT_SUPER",
-
-            _ => unreachable!(
-                "basic values are only control(2), shift(8), alt(4) and super(1)
-                    \r\n you did not present a valid combination of those values"
-            ),
+            // basic values are only control(2), shift(8), alt(4) and super(1)
+            _ => "INVALID",
         }
     }
 
-    fn from_or(bytes: &[u8]) -> Self {
-        let modifier = Self(bytes.into_iter().fold(0, |acc, b| acc | b));
-        assert!(modifier.0 < 16);
-
-        modifier
-    }
-
-    fn or(&mut self, byte: u8) {
-        assert!(self.0 | byte < 16);
-
-        self.0 |= byte;
+    pub(crate) fn or(&mut self, byte: u8) {
+        self.0 = (self.0 | byte) & 0x0f;
     }
 
     // 6 bytes escape sequence modifiers identification
     // the modifier byte is bytes[4] (the 5th byte)
     // in the inputted escape sequence
-    fn from_raw67(byte: u8) -> Self {
-        Self(match byte {
+    fn from_raw67(byte: u8) -> Option<Self> {
+        Some(Self(match byte {
             50 => SHIFT,
             51 => ALT,
             52 => SHIFT | ALT,
@@ -277,16 +472,28 @@ impl Modifiers {
             55 => CONTROL | ALT,
             56 => CONTROL | SHIFT | ALT,
             57 => SUPER,
-            _ => unreachable!(
-                "What manner of key combinations did 
-                you input to get this byte, sir?",
-            ),
-        })
+            _ => return None,
+        }))
+    }
+
+    /// makes the modifiers out of the modifiers parameter of an escape sequence,
+    /// e.g., the 5 in \x1b[1;5A or \x1b[105;5u
+    /// the parameter is 1 + the shift(1), alt(2), ctrl(4) and super(8) bits
+    /// the other bits, e.g., caps lock and num lock from the kitty protocol, are ignored
+    pub fn from_param(param: u16) -> Self {
+        let bits = param.saturating_sub(1);
+
+        Self(
+            [(1, SHIFT), (2, ALT), (4, CONTROL), (8, SUPER)]
+                .into_iter()
+                .filter(|(bit, _)| bits & bit != 0)
+                .fold(NONE, |acc, (_, m)| acc | m),
+        )
     }
 
     // 7 bytes escape sequence modifiers identification
-    fn from_super7(byte: u8) -> Self {
-        Self(match byte {
+    fn from_super7(byte: u8) -> Option<Self> {
+        Some(Self(match byte {
             48 => 9,
             49 => 5,
             50 => 13,
@@ -294,11 +501,21 @@ impl Modifiers {
             52 => 11,
             53 => 7,
             54 => 15,
-            val => unreachable!("all mods handled, who are you? {}", val),
-        })
+            _ => return None,
+        }))
     }
 }
 
+/// whether a key was pressed, held down or released
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum KeyKind {
+    #[default]
+    Press,
+    /// the key is being held down
+    Repeat,
+    Release,
+}
+
 /// the keyboard input event struct
 #[derive(Debug)]
 pub struct KbdEvent {
@@ -306,6 +523,16 @@ pub struct KbdEvent {
     pub char: Char,
     /// keyboard input event modifiers
     pub modifiers: Modifiers,
+    /// press, repeat or release
+    /// only the kitty keyboard protocol reports repeats and releases, anything else is a press
+    pub kind: KeyKind,
+    /// the key with shift applied, e.g., 'A' for 'a'
+    /// only reported by the kitty keyboard protocol, with the alternate keys flag
+    pub shifted: Option<char>,
+    /// the key at the same place on a us keyboard layout, e.g., 'c' for the russian 'с'
+    /// meant for shortcuts that work whatever the layout;
+    /// only reported by the kitty keyboard protocol, with the alternate keys flag
+    pub base: Option<char>,
 }
 
 impl Default for KbdEvent {
@@ -313,13 +540,16 @@ impl Default for KbdEvent {
         Self {
             modifiers: Modifiers(0x0),
             char: Char::CC(CC::ESC),
+            kind: KeyKind::Press,
+            shifted: None,
+            base: None,
         }
     }
 }
 
 impl KbdEvent {
     pub fn is_char(&self) -> bool {
-        if let Char::Char(_) = self.char {
+        if let Char::Char(_) | Char::Grapheme(_) = self.char {
             true
         } else {
             false
@@ -354,26 +584,41 @@ mod utf8_decoder {
             })
     }
 
+    // the error for bytes that are not a key this decoder knows of
+    fn unknown(bytes: &[u8]) -> InputError {
+        InputError::UnknownSequence(bytes.to_vec())
+    }
+
+    fn known<T>(value: Option<T>, bytes: &[u8]) -> Result<T, InputError> {
+        value.ok_or_else(|| unknown(bytes))
+    }
+
+    fn utf8(bytes: &[u8]) -> Result<Char, InputError> {
+        Char::from_utf8(bytes).ok_or_else(|| InputError::InvalidUtf8(bytes.to_vec()))
+    }
+
     /// the utf 8 value is only 1 byte long and can only have a 0 for its highest bit
     /// this effectively means it's an ascii value; either char or control char
     /// three cases to this:
     /// - ascii char
     /// - ascii control char (cr, bs, esc or tab)
     /// - ascii char with ctrl modifier
-    fn decode_1_byte(byte: u8, ke: &mut KbdEvent) {
-        match byte {
+    fn decode_1_byte(byte: u8, ke: &mut KbdEvent) -> Result<(), InputError> {
+        let char = match byte {
             0..=31 | 127 => match byte {
-                9 | 13 | 27 | 127 => ke.char = Char::from_cc(byte),
-                _val => {
-                    *ke = KbdEvent {
-                        char: Char::from_ctrl_ascii(byte),
-                        modifiers: Modifiers(CONTROL),
-                    }
+                8 | 9 | 13 | 27 | 127 => Char::from_cc(byte),
+                _ => {
+                    ke.modifiers = Modifiers(CONTROL);
+                    Char::from_ctrl_ascii(byte)
                 }
             },
-            32..=126 => ke.char = Char::from_ascii(byte),
-            _ => unreachable!("ascii are limited by 7 bits a byte, so from 0 to 127"),
-        }
+            32..=126 => Some(Char::from_ascii(byte)),
+            // ascii are limited by 7 bits a byte, so from 0 to 127
+            _ => None,
+        };
+        ke.char = known(char, &[byte])?;
+
+        Ok(())
     }
 
     /// three cases
@@ -382,154 +627,133 @@ mod utf8_decoder {
     /// - modifier byte (alt) followed by modifier (ctrl) altered ascii byte/value
     /// easy problem, since, in utf8 rules, a 2 bytes utf8 value MUST have a first byte that has
     /// 110 as its highest 3 bits and a second byte that has 10 as its highest 2 bits
-    fn decode_2_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 2);
+    fn decode_2_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
         // a 2 bytes utf8 value or an ascii + alt
         if is_utf82(bytes[0], bytes[1]) {
-            ke.char = Char::from_utf8(bytes);
-        } else {
-            assert_eq!(27, bytes[0]);
-            decode_1_byte(bytes[1], ke);
+            ke.char = utf8(bytes)?;
+        } else if bytes[0] == 27 {
+            decode_1_byte(bytes[1], ke).map_err(|_| unknown(bytes))?;
             ke.modifiers.or(ALT);
+        } else {
+            return Err(unknown(bytes));
         }
+
+        Ok(())
     }
 
     // cases
     // - 3 bytes utf8
     // - arrow key
     // - fn keys
-    fn decode_3_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 3);
+    fn decode_3_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
         // valid '3 bytes' utf8 first byte
         if is_utf83(bytes[0], bytes[1], bytes[2]) {
-            ke.char = Char::from_utf8(bytes);
+            ke.char = utf8(bytes)?;
         } else {
-            assert_eq!(bytes[0], 27);
-            match bytes[1] {
-                91 => {
-                    if bytes[2] == 90 {
-                        ke.modifiers.or(SHIFT);
-                        ke.char = Char::CC(CC::TAB);
-                    } else {
-                        assert!(bytes[2] >= 65 && bytes[2] <= 68);
-                        ke.char = Char::from_arrow_key(bytes[2]);
-                    }
+            let cc = match bytes[..2] {
+                [27, 91] if bytes[2] == 90 => {
+                    ke.modifiers.or(SHIFT);
+                    Some(CC::TAB)
                 }
-                79 => {
-                    assert!(bytes[2] >= 80 && bytes[2] <= 83);
-                    ke.char = Char::from_fn_key3(bytes[2]);
-                }
-                _val => unreachable!(
-                    "do not know of this byte combination: {:?}, 
-                what manner of key did you press, sir?",
-                    bytes
-                ),
-            }
+                [27, 91] if b"ABCDEFH".contains(&bytes[2]) => CC::from_csi(bytes[2], 1),
+                // application cursor and keypad keys too
+                [27, 79] => CC::from_ss3(bytes[2]),
+                _ => None,
+            };
+            ke.char = Char::CC(known(cc, bytes)?);
         }
+
+        Ok(())
     }
 
-    fn decode_4_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 4);
+    fn decode_4_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
         if is_utf84(bytes[0], bytes[1], bytes[2], bytes[3]) {
-            ke.char = Char::from_utf8(bytes);
+            ke.char = utf8(bytes)?;
+        } else if bytes[..2] == [27, 91] && bytes[3] == 126 {
+            ke.char = known(Char::from_cc_extra(bytes[2]), bytes)?;
         } else {
-            assert!(bytes[0] == 27 && bytes[1] == 91 && bytes[3] == 126);
-            ke.char = Char::from_cc_extra(bytes[2]);
+            return Err(unknown(bytes));
         }
+
+        Ok(())
     }
 
     // not utf8 anymore
-    fn decode_5_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 5);
-        assert!(
-            bytes[0] == 27 && bytes[1] == 91 && bytes[4] == 126,
-            "{bytes:?}"
-        );
-        assert!(bytes[2] == 50 || bytes[2] == 49);
-        ke.char = Char::from_fn_key5(bytes[2], bytes[3]);
+    fn decode_5_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
+        if bytes[..2] != [27, 91] || bytes[4] != 126 {
+            return Err(unknown(bytes));
+        }
+        ke.char = known(Char::from_fn_key5(bytes[2], bytes[3]), bytes)?;
+
+        Ok(())
     }
 
     // not utf8
     // 6 bytes < 7 means that there is not a modifiers combination of SUPER + mod(s)
-    fn decode_6_bytes(bytes: &[u8], ke: &mut KbdEvent) {
+    fn decode_6_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
         // escape sequence
-        assert_eq!(bytes.len(), 6);
-        assert!(bytes[0] == 27 && bytes[1] == 91);
-        assert!(bytes[3] == 59);
-        assert!([49, 51, 53, 54].contains(&bytes[2]));
-        if bytes[2] == 49 {
-            ke.modifiers = Modifiers::from_raw67(bytes[4]);
-            ke.char = if [65, 66, 67, 68].contains(&bytes[5]) {
-                Char::from_arrow_key(bytes[5])
-            } else if bytes[5] != 70 && bytes[5] != 72 {
-                assert!([80, 81, 82, 83].contains(&bytes[5]), "{bytes:?}");
-                Char::from_fn_key3(bytes[5])
-            } else {
-                assert!(bytes[5] == 70 || bytes[5] == 72);
-                if bytes[5] == 70 {
-                    Char::CC(CC::End)
-                } else {
-                    Char::CC(CC::Home)
-                }
-            }
-        } else {
-            assert!([51, 53, 54, 49].contains(&bytes[2]));
-            ke.modifiers = Modifiers::from_raw67(bytes[4]);
-            ke.char = if bytes[2] == 49 {
-                match bytes[5] {
-                    70 => Char::CC(CC::End),
-                    72 => Char::CC(CC::Home),
-                    _ => unreachable!("unhandled escape sequence; {:?}", bytes),
-                }
-            } else {
-                assert_eq!(bytes[5], 126);
-                Char::from_cc_extra(bytes[2])
-            }
+        if bytes[..2] != [27, 91] || bytes[3] != 59 {
+            return Err(unknown(bytes));
         }
+        ke.modifiers = known(Modifiers::from_raw67(bytes[4]), bytes)?;
+        let char = match (bytes[2], bytes[5]) {
+            (49, 65..=68) => Char::from_arrow_key(bytes[5]),
+            (49, 80..=83) => Char::from_fn_key3(bytes[5]),
+            (49, 70) => Some(Char::CC(CC::End)),
+            (49, 72) => Some(Char::CC(CC::Home)),
+            (50 | 51 | 53 | 54, 126) => Char::from_cc_extra(bytes[2]),
+            _ => None,
+        };
+        ke.char = known(char, bytes)?;
+
+        Ok(())
     }
 
-    fn decode_7_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 7);
+    fn decode_7_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
         // escape sequence
-        assert!(bytes[0] == 27 && bytes[1] == 91);
+        if bytes[..2] != [27, 91] {
+            return Err(unknown(bytes));
+        }
         match bytes[6] == 126 && bytes[4] == 59 {
             // fn key + some ctrl, shift, alt mods combination
             true => {
-                ke.modifiers = Modifiers::from_raw67(bytes[5]);
-                ke.char = Char::from_fn_key5(bytes[2], bytes[3]);
+                ke.modifiers = known(Modifiers::from_raw67(bytes[5]), bytes)?;
+                ke.char = known(Char::from_fn_key5(bytes[2], bytes[3]), bytes)?;
             }
             // arrow keys/extra cc + some super included mods combination
             false => {
-                assert_eq!(bytes[4], 49);
-                ke.modifiers = Modifiers::from_super7(bytes[5]);
-                match bytes[2] {
-                    49 if [65, 66, 67, 68].contains(&bytes[6]) => {
-                        ke.char = Char::from_arrow_key(bytes[6])
-                    }
-                    49 if bytes[6] == 70 => ke.char = Char::CC(CC::End),
-                    49 if bytes[6] == 72 => ke.char = Char::CC(CC::Home),
-                    51 | 53 | 54 if bytes[6] == 126 => ke.char = Char::from_cc_extra(bytes[2]),
-                    49 if [80, 81, 82, 83].contains(&bytes[6]) => {
-                        ke.char = Char::from_fn_key3(bytes[6])
-                    }
-                    _ => unreachable!("key combination: {:?} is unhandled", bytes),
+                if bytes[3] != 59 || bytes[4] != 49 {
+                    return Err(unknown(bytes));
                 }
+                ke.modifiers = known(Modifiers::from_super7(bytes[5]), bytes)?;
+                let char = match (bytes[2], bytes[6]) {
+                    (49, 65..=68) => Char::from_arrow_key(bytes[6]),
+                    (49, 70) => Some(Char::CC(CC::End)),
+                    (49, 72) => Some(Char::CC(CC::Home)),
+                    (50 | 51 | 53 | 54, 126) => Char::from_cc_extra(bytes[2]),
+                    (49, 80..=83) => Char::from_fn_key3(bytes[6]),
+                    _ => None,
+                };
+                ke.char = known(char, bytes)?;
             }
         }
+
+        Ok(())
     }
 
-    fn decode_8_bytes(bytes: &[u8], ke: &mut KbdEvent) {
-        assert_eq!(bytes.len(), 8);
-        assert!({
-            bytes[0] == 27
-                && bytes[1] == 91
-                && [49, 50].contains(&bytes[2])
-                && bytes[4] == 59
-                && bytes[5] == 49
-                && bytes[7] == 126
-        });
-        ke.modifiers = Modifiers::from_super7(bytes[6]);
-        ke.char = Char::from_fn_key5(bytes[2], bytes[3]);
+    fn decode_8_bytes(bytes: &[u8], ke: &mut KbdEvent) -> Result<(), InputError> {
+        if !(bytes[..2] == [27, 91]
+            && [49, 50, 51].contains(&bytes[2])
+            && bytes[4] == 59
+            && bytes[5] == 49
+            && bytes[7] == 126)
+        {
+            return Err(unknown(bytes));
+        }
+        ke.modifiers = known(Modifiers::from_super7(bytes[6]), bytes)?;
+        ke.char = known(Char::from_fn_key5(bytes[2], bytes[3]), bytes)?;
+
+        Ok(())
     }
 
     // WARN: design flow
@@ -542,23 +766,24 @@ mod utf8_decoder {
     // INFO: this only works under the premise of receiving 1 input event at once
     // basically no ctrl-v or any way to dump multiple chars of input at once
     /// decodes the bytes from 1 keyboard input event into a KbdEvent instance
-    pub fn decode_ki(bytes: &[u8]) -> Option<KbdEvent> {
+    /// returns an InputError if the bytes are not a key this decoder knows of
+    pub fn decode_ki(bytes: &[u8]) -> Result<KbdEvent, InputError> {
         let mut ke: KbdEvent = Default::default();
         match bytes.len() {
-            1 => decode_1_byte(bytes[0], &mut ke),
-            2 => decode_2_bytes(bytes, &mut ke),
-            3 => decode_3_bytes(bytes, &mut ke),
-            4 => decode_4_bytes(bytes, &mut ke),
-            5 => decode_5_bytes(bytes, &mut ke),
-            6 => decode_6_bytes(bytes, &mut ke),
-            7 => decode_7_bytes(bytes, &mut ke),
-            8 => decode_8_bytes(bytes, &mut ke),
-            _ => return None,
+            0 => return Err(InputError::Incomplete(vec![])),
+            1 => decode_1_byte(bytes[0], &mut ke)?,
+            2 => decode_2_bytes(bytes, &mut ke)?,
+            3 => decode_3_bytes(bytes, &mut ke)?,
+            4 => decode_4_bytes(bytes, &mut ke)?,
+            5 => decode_5_bytes(bytes, &mut ke)?,
+            6 => decode_6_bytes(bytes, &mut ke)?,
+            7 => decode_7_bytes(bytes, &mut ke)?,
+            8 => decode_8_bytes(bytes, &mut ke)?,
+            _ => return Err(unknown(bytes)),
         }
 
-        Some(ke)
+        Ok(ke)
     }
-
     // utf8 2 - 3 - 4 bytes values are easy to tell
     // the problem is ascii, it is hard to tell if a value is just an ascii or an escape sequence
     // 2 bytes esc seqs are always 27 then ascii byte
@@ -582,8 +807,8 @@ mod utf8_decoder {
     // as this fn can handle a ctrl-v of input
     /// decodes a vector of some number of keyboard input events bytes
     /// returns a vector of KbdEvents wrapped in Results
-    pub fn decode_ki_kai(bytes: Vec<u8>) -> Vec<Result<KbdEvent, Error>> {
-        let mut v: Vec<Result<KbdEvent, Error>> = vec![];
+    pub fn decode_ki_kai(bytes: Vec<u8>) -> Vec<Result<KbdEvent, InputError>> {
+        let mut v: Vec<Result<KbdEvent, InputError>> = vec![];
 
         let mut bytes = bytes.into_iter();
         while let Some(b0) = bytes.next() {
@@ -595,6 +820,7 @@ mod utf8_decoder {
                         v.push(Ok(KbdEvent {
                             char: Char::CC(CC::ESC),
                             modifiers: Modifiers::from_byte(0),
+                            ..Default::default()
                         }));
 
                         return v;
@@ -605,49 +831,34 @@ mod utf8_decoder {
                     let b2 = bytes.next();
 
                     if b2.is_none() {
-                        let mut ke = Default::default();
-                        decode_2_bytes(&[b0, b1], &mut ke);
-                        v.push(Ok(ke));
+                        v.push(decode_ki(&[b0, b1]));
 
                         return v;
                     }
 
                     let b2 = b2.unwrap();
 
-                    match b1 == 91 || b1 == 79 {
+                    match b1 {
                         // surely 3 bytes
-                        true if b1 == 79 => {
-                            assert!((80..=83).contains(&b2));
-                            let mut ke = Default::default();
-                            decode_3_bytes(&[b0, b1, b2], &mut ke);
-
-                            v.push(Ok(ke));
-                        }
+                        79 => v.push(decode_ki(&[b0, b1, b2])),
                         // 3 to 8 bytes esc seq
-                        true if b1 == 91 => {
-                            let mut ke = Default::default();
+                        91 => {
                             let b3 = bytes.next();
                             if b3.is_none() {
-                                decode_3_bytes(&[b0, b1, b2], &mut ke);
-                                v.push(Ok(ke));
+                                v.push(decode_ki(&[b0, b1, b2]));
 
                                 return v;
                             }
 
                             let b3 = b3.unwrap();
                             match b3 {
-                                126 => {
-                                    decode_4_bytes(&[b0, b1, b2, b3], &mut ke);
-                                    v.push(Ok(ke));
-                                }
+                                126 => v.push(decode_ki(&[b0, b1, b2, b3])),
 
                                 59 => {
                                     let b4 = bytes.next();
                                     let b5 = bytes.next();
                                     if b4.is_none() || b5.is_none() {
-                                        v.push(Err(Error::other(
-                                            "there should have been a 5th and 6th bytes here",
-                                        )));
+                                        v.push(Err(InputError::Incomplete(vec![b0, b1, b2, b3])));
                                         return v;
                                     }
 
@@ -656,8 +867,7 @@ mod utf8_decoder {
                                     let b6 = bytes.next();
 
                                     if b6.is_none() {
-                                        decode_6_bytes(&[b0, b1, b2, b3, b4, b5], &mut ke);
-                                        v.push(Ok(ke));
+                                        v.push(decode_ki(&[b0, b1, b2, b3, b4, b5]));
 
                                         return v;
                                     }
@@ -666,70 +876,59 @@ mod utf8_decoder {
 
                                     match b6 {
                                         126 | 80..=83 | 70 | 72 | 65..=68 => {
-                                            decode_7_bytes(&[b0, b1, b2, b3, b4, b5, b6], &mut ke);
-                                            v.push(Ok(ke));
+                                            v.push(decode_ki(&[b0, b1, b2, b3, b4, b5, b6]))
                                         }
                                         _ => {
-                                            decode_6_bytes(&[b0, b1, b2, b3, b4, b5], &mut ke);
+                                            v.push(decode_ki(&[b0, b1, b2, b3, b4, b5]));
                                             bytes = [b6]
                                                 .into_iter()
                                                 .chain(bytes)
                                                 .collect::<Vec<u8>>()
                                                 .into_iter();
-                                            v.push(Ok(ke));
                                         }
                                     }
                                 }
                                 _ => {
                                     let b4 = bytes.next();
                                     if b4.is_none() {
-                                        v.push(Err(Error::other(
-                                            "there should have been a 5th and 6th bytes here",
-                                        )));
+                                        v.push(Err(InputError::Incomplete(vec![b0, b1, b2, b3])));
                                         return v;
                                     }
                                     let b4 = b4.unwrap();
 
                                     match b4 == 126 {
-                                        true => {
-                                            decode_5_bytes(&[b0, b1, b2, b3, b4], &mut ke);
-                                            v.push(Ok(ke));
-                                        }
+                                        true => v.push(decode_ki(&[b0, b1, b2, b3, b4])),
                                         false => {
                                             let (b5, b6) = (bytes.next(), bytes.next());
                                             if b5.is_none() || b6.is_none() {
-                                                v.push(Err(Error::other(
-                                            "there should have been a 5th and 6th bytes here",
-                                        )));
+                                                v.push(Err(InputError::Incomplete(vec![
+                                                    b0, b1, b2, b3, b4,
+                                                ])));
                                                 return v;
                                             }
 
                                             let (b5, b6) = (b5.unwrap(), b6.unwrap());
                                             match b6 {
                                                 126 => {
-                                                    decode_7_bytes(
-                                                        &[b0, b1, b2, b3, b4, b5, b6],
-                                                        &mut ke,
-                                                    );
-                                                    v.push(Ok(ke));
+                                                    v.push(decode_ki(&[b0, b1, b2, b3, b4, b5, b6]))
                                                 }
                                                 48..=54 => {
                                                     let b7 = bytes.next();
                                                     if b7.is_none() {
-                                                        v.push(Err(Error::other(
-                                            "there should have been a 5th and 6th bytes here",
-                                        )));
+                                                        v.push(Err(InputError::Incomplete(vec![
+                                                            b0, b1, b2, b3, b4, b5, b6,
+                                                        ])));
                                                         return v;
                                                     }
                                                     let b7 = b7.unwrap();
-                                                    decode_8_bytes(
-                                                        &[b0, b1, b2, b3, b4, b5, b6, b7],
-                                                        &mut ke,
-                                                    );
-                                                    v.push(Ok(ke));
+                                                    v.push(decode_ki(&[
+                                                        b0, b1, b2, b3, b4, b5, b6, b7,
+                                                    ]));
                                                 }
                                                 _ => {
-                                                    unreachable!("you cant get here: bytes7 = {b6}")
+                                                    v.push(Err(InputError::UnknownSequence(vec![
+                                                        b0, b1, b2, b3, b4, b5, b6,
+                                                    ])))
                                                 }
                                             }
                                         }
@@ -737,10 +936,11 @@ mod utf8_decoder {
                                 }
                             }
                         }
-                        false => {
+                        _ => {
                             v.push(Ok(KbdEvent {
                                 char: Char::CC(CC::ESC),
                                 modifiers: Modifiers::from_byte(0),
+                                ..Default::default()
                             }));
                             bytes = [b1, b2]
                                 .into_iter()
@@ -748,21 +948,15 @@ mod utf8_decoder {
                                 .collect::<Vec<u8>>()
                                 .into_iter();
                         }
-                        _ => unreachable!("im impossible to reach"),
                     }
                 }
                 // this arm covers all utf8 possibilities
                 false => match is_utf81(b0) {
-                    true => v.push(Ok({
-                        let mut ke = Default::default();
-                        decode_1_byte(b0, &mut ke);
-
-                        ke
-                    })),
+                    true => v.push(decode_ki(&[b0])),
                     false => {
                         let b1 = bytes.next();
                         if b1.is_none() {
-                            v.push(Err(Error::other("not a valid utf8 nor an esc seq")));
+                            v.push(Err(InputError::InvalidUtf8(vec![b0])));
 
                             return v;
                         }
@@ -770,17 +964,12 @@ mod utf8_decoder {
                         let b1 = b1.unwrap();
 
                         match is_utf82(b0, b1) {
-                            true => {
-                                let mut ke = Default::default();
-                                decode_2_bytes(&[b0, b1], &mut ke);
-
-                                v.push(Ok(ke));
-                            }
+                            true => v.push(decode_ki(&[b0, b1])),
                             false => {
                                 let b2 = bytes.next();
 
                                 if b2.is_none() {
-                                    v.push(Err(Error::other("not a valid utf8 nor an esc seq")));
+                                    v.push(Err(InputError::InvalidUtf8(vec![b0, b1])));
 
                                     return v;
                                 }
@@ -788,19 +977,12 @@ mod utf8_decoder {
                                 let b2 = b2.unwrap();
 
                                 match is_utf83(b0, b1, b2) {
-                                    true => {
-                                        let mut ke = Default::default();
-                                        decode_3_bytes(&[b0, b1, b2], &mut ke);
-
-                                        v.push(Ok(ke));
-                                    }
+                                    true => v.push(decode_ki(&[b0, b1, b2])),
                                     false => {
                                         let b3 = bytes.next();
 
                                         if b3.is_none() {
-                                            v.push(Err(Error::other(
-                                                "not a valid utf8 nor an esc seq",
-                                            )));
+                                            v.push(Err(InputError::InvalidUtf8(vec![b0, b1, b2])));
 
                                             return v;
                                         }
@@ -808,15 +990,10 @@ mod utf8_decoder {
                                         let b3 = b3.unwrap();
 
                                         match is_utf84(b0, b1, b2, b3) {
-                                            true => {
-                                                let mut ke = Default::default();
-                                                decode_4_bytes(&[b0, b1, b2, b3], &mut ke);
-
-                                                v.push(Ok(ke));
-                                            }
-                                            false => v.push(Err(Error::other(
-                                                "not a valid utf8 nor an esc seq",
-                                            ))),
+                                            true => v.push(decode_ki(&[b0, b1, b2, b3])),
+                                            false => v.push(Err(InputError::InvalidUtf8(vec![
+                                                b0, b1, b2, b3,
+                                            ]))),
                                         }
                                     }
                                 }
@@ -835,81 +1012,18 @@ mod utf8_decoder {
     }
 
     fn is_utf82(byte1: u8, byte2: u8) -> bool {
-        (byte1 >= 192 && byte1 < 224) && (byte2 >= 128 && byte2 < 192)
+        (192..224).contains(&byte1) && (128..192).contains(&byte2)
     }
 
     fn is_utf83(b1: u8, b2: u8, b3: u8) -> bool {
-        (b1 >= 224 && b1 < 240) && (b2 >= 128 && b2 < 192) && (b3 >= 128 && b3 < 192)
+        (224..240).contains(&b1) && (128..192).contains(&b2) && (128..192).contains(&b3)
     }
 
     fn is_utf84(b1: u8, b2: u8, b3: u8, b4: u8) -> bool {
-        (b1 >= 240 && b1 < 248)
-            && (b2 >= 128 && b2 < 192)
-            && (b3 >= 128 && b3 < 192)
-            && (b4 >= 128 && b4 < 192)
-    }
-
-    // utf8 string decoder
-    // since the module is called utf8_decoder
-    // returns error if it doesnt find the expected utf8 bytes
-    // crashes if decoding bytes into utf8 str with std::str::from_utf8 returns an error
-    pub fn decode_utf8_string(bytes: &[u8]) -> Result<String, std::io::Error> {
-        let mut s = String::new();
-        let mut bytes = bytes.into_iter();
-
-        while let Some(byte) = bytes.next() {
-            // check how many bytes the next utf8 char carries
-            match is_utf81(*byte) {
-                true => s.push(*byte as char),
-                false => {
-                    let b2 = bytes.next();
-                    let b2 = if b2.is_none() {
-                        return Err(std::io::Error::other("not a valid utf8 string"));
-                    } else {
-                        b2.unwrap()
-                    };
-
-                    match is_utf82(*byte, *b2) {
-                        true => {
-                            s.push_str(std::str::from_utf8(&[*byte, *b2]).expect("not valid utf8"))
-                        }
-                        false => {
-                            let b3 = bytes.next();
-                            let b3 = if b3.is_none() {
-                                return Err(std::io::Error::other("not a valid utf8 string"));
-                            } else {
-                                b3.unwrap()
-                            };
-
-                            match is_utf83(*byte, *b2, *b3) {
-                                true => s.push_str(
-                                    std::str::from_utf8(&[*byte, *b2, *b3])
-                                        .expect("not valid utf8"),
-                                ),
-                                false => {
-                                    let b4 = bytes.next();
-                                    let b4 = if b4.is_none() {
-                                        return Err(std::io::Error::other(
-                                            "not a valid utf8 string",
-                                        ));
-                                    } else {
-                                        b4.unwrap()
-                                    };
-
-                                    assert!(is_utf84(*byte, *b2, *b3, *b4));
-                                    s.push_str(
-                                        std::str::from_utf8(&[*byte, *b2, *b3, *b4])
-                                            .expect("not valid utf8"),
-                                    )
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(s)
+        (240..248).contains(&b1)
+            && (128..192).contains(&b2)
+            && (128..192).contains(&b3)
+            && (128..192).contains(&b4)
     }
 }
 
@@ -0,0 +1,152 @@
+use std::io::Write;
+
+use super::keyboard::{Char, KbdEvent, KeyKind, Modifiers, CC, SHIFT};
+
+// the progressive enhancement flags, from https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+/// keys that are ambiguous in the legacy encoding, e.g., esc, ctrl+i vs tab or ctrl+m vs enter,
+/// come as CSI u sequences
+pub const DISAMBIGUATE: u8 = 1;
+/// repeat and release events get reported too
+pub const EVENT_TYPES: u8 = 2;
+/// the shifted and base layout keys get reported along with the key
+pub const ALTERNATE_KEYS: u8 = 4;
+/// every key comes as a CSI u sequence, plain text included
+pub const ALL_KEYS: u8 = 8;
+/// the text the key would have typed gets reported along with the key
+pub const ASSOCIATED_TEXT: u8 = 16;
+
+/// pushes the given kitty keyboard protocol flags on the terminal's keyboard mode stack
+/// terminals without the protocol ignore this and keep sending legacy sequences,
+/// which are still decoded as before
+/// the main and alternate screens have separate stacks,
+/// so the flags have to be pushed after entering the alternate screen
+pub fn enable_kitty_keyboard(writer: &mut impl Write, flags: u8) {
+    _ = writer.write(format!("\x1b[>{}u", flags).as_bytes());
+}
+
+/// pops the flags pushed by enable_kitty_keyboard, giving back the previous keyboard mode
+pub fn disable_kitty_keyboard(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[<u");
+}
+
+// the numbers of a ':' separated field, missing ones are None
+fn field(bytes: Option<&[u8]>) -> Vec<Option<u32>> {
+    bytes
+        .unwrap_or_default()
+        .split(|b| *b == b':')
+        .map(|n| std::str::from_utf8(n).ok()?.parse().ok())
+        .collect()
+}
+
+// kitty sends the keys that have no unicode codepoint as private use area codepoints,
+// CC::from_kitty tells them apart
+fn key_char(code: u32) -> Option<char> {
+    match code {
+        0xe000..=0xf8ff => None,
+        _ => char::from_u32(code),
+    }
+}
+
+// the key of a unicode codepoint, control keys included
+pub(super) fn unicode_key(code: u32) -> Option<Char> {
+    Some(match code {
+        9 => Char::CC(CC::TAB),
+        13 => Char::CC(CC::CR),
+        27 => Char::CC(CC::ESC),
+        127 => Char::CC(CC::BS),
+        0xe000..=0xf8ff => Char::CC(CC::from_kitty(code)?),
+        _ => Char::Char(key_char(code)?),
+    })
+}
+
+/// decodes a single kitty keyboard protocol sequence into a KbdEvent
+/// CSI code[:shifted[:base]] [; modifiers[:event type] [; text]] u
+/// or a legacy CSI sequence with the same parameters, e.g., CSI 1;1:3A for an up arrow release
+/// returns None if the sequence is malformed or the key is not one this crate knows of
+pub fn decode(seq: &[u8]) -> Option<KbdEvent> {
+    let (&fin, body) = seq.strip_prefix(b"\x1b[")?.split_last()?;
+    // a private marker makes it a reply, e.g., \x1b[?1u for the flags query
+    if matches!(body.first(), Some(b'<'..=b'?')) {
+        return None;
+    }
+
+    let mut fields = body.split(|b| *b == b';');
+    let keys = field(fields.next());
+    let mods = field(fields.next());
+
+    let char = match fin {
+        b'u' => unicode_key(keys[0]?)?,
+        // the legacy sequences leave out a code of 1
+        _ => Char::CC(CC::from_csi(fin, keys[0].unwrap_or(1))?),
+    };
+
+    let mut modifiers = Modifiers::from_param(mods[0].unwrap_or(1) as u16);
+    // shift + tab has a final byte of its own
+    if fin == b'Z' {
+        modifiers.or(SHIFT);
+    }
+
+    let kind = match mods.get(1).copied().flatten() {
+        Some(2) => KeyKind::Repeat,
+        Some(3) => KeyKind::Release,
+        _ => KeyKind::Press,
+    };
+
+    Some(KbdEvent {
+        char,
+        modifiers,
+        kind,
+        shifted: keys.get(1).copied().flatten().and_then(key_char),
+        base: keys.get(2).copied().flatten().and_then(key_char),
+    })
+}
+
+#[cfg(test)]
+mod kitty {
+    use super::decode;
+    use crate::inputs::keyboard::{Char, KeyKind, Modifiers, CC};
+
+    #[test]
+    fn keys() {
+        [
+            (&b"\x1b[97u"[..], Char::Char('a'), 0, KeyKind::Press),
+            (b"\x1b[105;5u", Char::Char('i'), 2, KeyKind::Press),
+            (b"\x1b[9u", Char::CC(CC::TAB), 0, KeyKind::Press),
+            (b"\x1b[13;2u", Char::CC(CC::CR), 8, KeyKind::Press),
+            (b"\x1b[27u", Char::CC(CC::ESC), 0, KeyKind::Press),
+            (b"\x1b[97;1:3u", Char::Char('a'), 0, KeyKind::Release),
+            (b"\x1b[1;1:2A", Char::CC(CC::Up), 0, KeyKind::Repeat),
+            (b"\x1b[1;7:3D", Char::CC(CC::Left), 6, KeyKind::Release),
+            (b"\x1b[5;9~", Char::CC(CC::PageUp), 1, KeyKind::Press),
+        ]
+        .into_iter()
+        .for_each(|(seq, char, modifiers, kind)| {
+            let ke = decode(seq).unwrap();
+            assert_eq!(ke.char, char, "{:?}", seq);
+            assert_eq!(ke.modifiers, Modifiers(modifiers), "{:?}", seq);
+            assert_eq!(ke.kind, kind, "{:?}", seq);
+        });
+
+        // private use area keys that are not known, e.g., caps lock
+        assert!(decode(b"\x1b[57358u").is_none());
+        // a flags query reply, and no key code at all
+        assert!(decode(b"\x1b[?1u").is_none());
+        assert!(decode(b"\x1b[u").is_none());
+        assert!(decode(b"\x1b[x;5u").is_none());
+        // a cursor position report, not ctrl + f3
+        assert!(decode(b"\x1b[12;5R").is_none());
+        assert_eq!(decode(b"\x1b[1;5R").unwrap().char, Char::CC(CC::F3));
+    }
+
+    #[test]
+    fn alternate_keys() {
+        let ke = decode(b"\x1b[97:65;2u").unwrap();
+        assert_eq!(ke.shifted, Some('A'));
+        assert_eq!(ke.base, None);
+
+        // cyrillic es, on the us layout c key
+        let ke = decode(b"\x1b[1089::99;5u").unwrap();
+        assert_eq!(ke.char, Char::Char('с'));
+        assert_eq!(ke.base, Some('c'));
+    }
+}
@@ -1,6 +1,7 @@
-use std::io::{StdoutLock, Write};
+use std::io::Write;
 
 use crate::inputs::keyboard::Modifiers;
+use crate::inputs::InputError;
 
 /// Mouse event struct
 /// contains the mouse gesture, modifiers and the [x,y] coordinates of the cursor
@@ -44,68 +45,65 @@ pub enum Gesture {
 }
 
 /// decodes input bytes into a vec of mouse events
-pub fn decode_mi(bytes: &[u8]) -> Vec<MouseEvent> {
-    let mut rem = bytes.len();
-    assert_eq!(rem % 6, 0);
+/// returns an InputError if the bytes are not whole mouse reports this decoder knows of
+pub fn decode_mi(bytes: &[u8]) -> Result<Vec<MouseEvent>, InputError> {
+    if !bytes.len().is_multiple_of(6) {
+        return Err(InputError::Incomplete(bytes.to_vec()));
+    }
 
     let mut v: Vec<MouseEvent> = Vec::new();
 
     let mut me = MouseEvent::default();
-    let mut bytes = bytes.into_iter();
-
-    while rem != 0 {
-        let eve = (0..6)
-            .into_iter()
-            .map(|_| bytes.next())
-            .filter(|o| o.is_some())
-            .map(|o| *o.unwrap())
-            .collect::<Vec<u8>>();
-
-        decode_6_bytes(eve, &mut me);
+    for eve in bytes.chunks_exact(6) {
+        decode_6_bytes(eve, &mut me)?;
 
         v.push(me.clone());
-
-        rem -= 6;
     }
 
-    v
+    Ok(v)
 }
 
 // th fourth byte; bytes[3] denotes the action being taken as well as the modifiers
 // the last 2 bytes are for cursor position (x, y)
 // the cursor position returned always start from 33 so should remove 33 from both x and y
 // to get the correct position
-fn decode_6_bytes(bytes: Vec<u8>, me: &mut MouseEvent) {
-    // assert mouse escape sequence
-    assert_eq!(bytes[0], 27);
-    assert_eq!(bytes[1], 91);
-    assert_eq!(bytes[2], 77);
-    me.modifiers = mouse_modifiers(bytes[3]);
-    me.gesture = mouse_gesture(bytes[3], bytes[4], bytes[5]);
-    me.position = [bytes[4] - 33, bytes[5] - 33];
+fn decode_6_bytes(bytes: &[u8], me: &mut MouseEvent) -> Result<(), InputError> {
+    let unknown = || InputError::UnknownSequence(bytes.to_vec());
+    // mouse escape sequence
+    if bytes[..3] != [27, 91, 77] {
+        return Err(unknown());
+    }
+    me.modifiers = mouse_modifiers(bytes[3]).ok_or_else(unknown)?;
+    me.gesture = mouse_gesture(bytes[3], bytes[4], bytes[5]).ok_or_else(unknown)?;
+    me.position = match (bytes[4].checked_sub(33), bytes[5].checked_sub(33)) {
+        (Some(x), Some(y)) => [x, y],
+        _ => return Err(unknown()),
+    };
+
+    Ok(())
 }
 
-fn mouse_gesture(byte: u8, bx: u8, by: u8) -> Gesture {
-    match byte {
+fn mouse_gesture(byte: u8, bx: u8, by: u8) -> Option<Gesture> {
+    Some(match byte {
         35 | 43 | 51 | 59 => Gesture::Release,
         67 | 71 | 75 | 83 | 87 | 79 | 91 | 95 => Gesture::None,
         32 | 40 | 48 | 56 => Gesture::LeftPress,
         33 | 41 | 49 | 57 => Gesture::WheelePress,
         34 | 50 | 52 | 48 => Gesture::RightPress,
+        // these codes rejuvenate in cycles of 4
         96..=123 => match byte % 4 {
             0 => Gesture::WheeleDown,
             1 => Gesture::WheeleUp,
             2 => Gesture::WheeleRight,
-            3 => Gesture::WheeleLeft,
-            _ => unreachable!("these codes rejuvenate in cycles of 4"),
+            _ => Gesture::WheeleLeft,
         },
-        _ => unreachable!("all possible cases have been handled"),
-    }
+        _ => return None,
+    })
 }
 
-fn mouse_modifiers(byte: u8) -> Modifiers {
+fn mouse_modifiers(byte: u8) -> Option<Modifiers> {
     // shift + 3 finger/wheele click does a paste
-    match byte {
+    Some(match byte {
         67 | 32..=35 | 96..=99 => Modifiers(0),
         83 | 48..=51 | 112..=115 => Modifiers(2),
         71 | 100..=103 => Modifiers(8),
@@ -114,14 +112,14 @@ fn mouse_modifiers(byte: u8) -> Modifiers {
         91 | 56..=58 | 120..=123 => Modifiers(6), // ctrl + alt
         79 | 108..=111 => Modifiers(12),          // shift + alt
         95 | 124..=127 => Modifiers(15),
-        _ => unreachable!("all possible cases have been handled"),
-    }
+        _ => return None,
+    })
 }
 
 /// enables raw mouse input reading
 /// when this function is called, the prgram can start reading raw mouse input events
 /// which can then be used in the program
-pub fn enable_mouse_input(writer: &mut StdoutLock) {
+pub fn enable_mouse_input(writer: &mut impl Write) {
     // NOTE: the following line enables the terminal to receive mouse events
     _ = writer.write(b"\x1b[?1003h");
 }
@@ -129,6 +127,6 @@ pub fn enable_mouse_input(writer: &mut StdoutLock) {
 /// disables raw mouse input reading
 /// makes the mouse unusable in the program
 /// once this is called, the terminal emulator will start detecting mouse input events again
-pub fn disable_mouse_input(writer: &mut StdoutLock) {
+pub fn disable_mouse_input(writer: &mut impl Write) {
     _ = writer.write(b"\x1b[?1003l");
 }
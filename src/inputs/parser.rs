@@ -0,0 +1,450 @@
+// the bracketed paste delimiters, from \x1b[?2004h
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// a whole input sequence, as split out of the input bytes by the Parser
+#[derive(Debug, PartialEq)]
+pub enum Sequence {
+    /// a utf8 char or a control byte, e.g., b"a", b"\r" or "é".as_bytes()
+    Char(Vec<u8>),
+    /// an esc followed by a utf8 char or a control byte, i.e., that key with alt
+    /// holds the esc too
+    Alt(Vec<u8>),
+    /// a lone esc
+    Esc,
+    /// a control sequence, e.g., b"\x1b[A" or b"\x1b[105;5u"
+    Csi(Vec<u8>),
+    /// a single shift 3 sequence, e.g., b"\x1bOP" for f1
+    Ss3(Vec<u8>),
+    /// an x10 mouse report, b"\x1b[M" followed by 3 raw bytes
+    Mouse(Vec<u8>),
+    /// the body of an operating system command, without the delimiters, e.g., a clipboard reply
+    Osc(Vec<u8>),
+    /// the body of a device control string, without the delimiters, e.g., an XTVERSION reply
+    Dcs(Vec<u8>),
+    /// the text of a bracketed paste, without the delimiters
+    Paste(Vec<u8>),
+    /// bytes that make no sequence, e.g., a stray utf8 continuation byte,
+    /// a utf8 char that got cut short or a control sequence that got interrupted
+    Invalid(Vec<u8>),
+}
+
+impl Sequence {
+    /// the bytes of the sequence as they were read, delimiters included
+    /// osc and dcs strings get ST as their terminator, whichever one they came with
+    pub fn into_bytes(self) -> Vec<u8> {
+        let (start, body, end): (&[u8], Vec<u8>, &[u8]) = match self {
+            Sequence::Esc => (b"\x1b", vec![], b""),
+            Sequence::Osc(body) => (b"\x1b]", body, b"\x1b\\"),
+            Sequence::Dcs(body) => (b"\x1bP", body, b"\x1b\\"),
+            Sequence::Paste(text) => (PASTE_START, text, PASTE_END),
+            Sequence::Char(bytes)
+            | Sequence::Alt(bytes)
+            | Sequence::Csi(bytes)
+            | Sequence::Ss3(bytes)
+            | Sequence::Mouse(bytes)
+            | Sequence::Invalid(bytes) => (b"", bytes, b""),
+        };
+
+        [start, &body, end].concat()
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum State {
+    #[default]
+    Ground,
+    Esc,
+    /// the number of continuation bytes the utf8 char still needs
+    Utf8(usize),
+    Csi,
+    Mouse,
+    Ss3,
+    Osc,
+    Dcs,
+    Paste,
+}
+
+/// a streaming vt input parser
+/// splits the input bytes into whole sequences, in the order they came in;
+/// a sequence that is split between two reads is kept until the rest of it arrives
+///
+/// # Examples
+/// ```
+/// use bararaq::inputs::parser::{Parser, Sequence};
+///
+/// let mut parser = Parser::new();
+/// assert_eq!(parser.parse(b"a\x1b[1;5"), vec![Sequence::Char(b"a".to_vec())]);
+/// assert_eq!(parser.parse(b"A"), vec![Sequence::Csi(b"\x1b[1;5A".to_vec())]);
+/// ```
+#[derive(Debug, Default)]
+pub struct Parser {
+    state: State,
+    // the bytes of the sequence being parsed
+    pending: Vec<u8>,
+}
+
+impl Parser {
+    /// creates a new parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// whether the parser is in the middle of a bracketed paste
+    pub fn is_pasting(&self) -> bool {
+        self.state == State::Paste
+    }
+
+    /// whether the last parsed bytes are an esc that may still start a sequence,
+    /// on its own or with an introducer, e.g., \x1b[ which is also alt + [
+    pub fn is_esc(&self) -> bool {
+        match self.state {
+            State::Esc | State::Ss3 => true,
+            State::Csi => self.pending == b"\x1b[",
+            State::Osc | State::Dcs => self.pending.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// takes the pending esc out as a lone esc,
+    /// or as the alt key of the introducer that follows it
+    /// returns None if the last parsed bytes were not an esc
+    pub fn flush_esc(&mut self) -> Option<Sequence> {
+        if !self.is_esc() {
+            return None;
+        }
+        let seq = match self.state {
+            State::Csi | State::Ss3 => Sequence::Alt(std::mem::take(&mut self.pending)),
+            State::Osc => Sequence::Alt(b"\x1b]".to_vec()),
+            State::Dcs => Sequence::Alt(b"\x1bP".to_vec()),
+            _ => Sequence::Esc,
+        };
+        self.reset();
+
+        Some(seq)
+    }
+
+    fn reset(&mut self) {
+        self.state = State::Ground;
+        self.pending.clear();
+    }
+
+    // ends the pending sequence as the given one
+    fn emit(&mut self, v: &mut Vec<Sequence>, seq: fn(Vec<u8>) -> Sequence) {
+        self.state = State::Ground;
+        v.push(seq(std::mem::take(&mut self.pending)));
+    }
+
+    /// parses the input bytes, returns every sequence they complete
+    pub fn parse(&mut self, bytes: &[u8]) -> Vec<Sequence> {
+        let mut v = vec![];
+        for b in bytes {
+            self.advance(*b, &mut v);
+        }
+
+        v
+    }
+
+    fn advance(&mut self, b: u8, v: &mut Vec<Sequence>) {
+        match self.state {
+            State::Ground => match b {
+                27 => {
+                    self.state = State::Esc;
+                    self.pending.push(b);
+                }
+                0..=127 => v.push(Sequence::Char(vec![b])),
+                _ => self.utf8(b, v),
+            },
+            State::Esc => match b {
+                b'[' => {
+                    self.state = State::Csi;
+                    self.pending.push(b);
+                }
+                b'O' => {
+                    self.state = State::Ss3;
+                    self.pending.push(b);
+                }
+                b']' => {
+                    self.state = State::Osc;
+                    self.pending.clear();
+                }
+                b'P' => {
+                    self.state = State::Dcs;
+                    self.pending.clear();
+                }
+                // a lone esc followed by a new sequence
+                27 => v.push(Sequence::Esc),
+                0..=127 => {
+                    self.pending.push(b);
+                    self.emit(v, Sequence::Alt);
+                }
+                _ => self.utf8(b, v),
+            },
+            State::Utf8(need) => match b {
+                0x80..=0xbf => {
+                    self.pending.push(b);
+                    if need > 1 {
+                        self.state = State::Utf8(need - 1);
+                    } else if self.pending[0] == 27 {
+                        self.emit(v, Sequence::Alt);
+                    } else {
+                        self.emit(v, Sequence::Char);
+                    }
+                }
+                // the char got cut short, the byte starts something else
+                _ => {
+                    self.emit(v, Sequence::Invalid);
+                    self.advance(b, v);
+                }
+            },
+            State::Csi => match b {
+                b'M' if self.pending == b"\x1b[" => {
+                    self.state = State::Mouse;
+                    self.pending.push(b);
+                }
+                0x20..=0x3f => self.pending.push(b),
+                0x40..=0x7e => {
+                    self.pending.push(b);
+                    if self.pending == PASTE_START {
+                        self.state = State::Paste;
+                        self.pending.clear();
+                    } else {
+                        self.emit(v, Sequence::Csi);
+                    }
+                }
+                // not a valid sequence, the byte starts something else
+                // a bare introducer was alt + [ followed by another key
+                _ => {
+                    match self.pending == b"\x1b[" {
+                        true => self.emit(v, Sequence::Alt),
+                        false => self.emit(v, Sequence::Invalid),
+                    }
+                    self.advance(b, v);
+                }
+            },
+            State::Mouse => {
+                self.pending.push(b);
+                if self.pending.len() == 6 {
+                    self.emit(v, Sequence::Mouse);
+                }
+            }
+            State::Ss3 => match b {
+                // no ss3 sequence ends with a control byte,
+                // so it was alt + O followed by another key
+                0..=31 => {
+                    self.emit(v, Sequence::Alt);
+                    self.advance(b, v);
+                }
+                _ => {
+                    self.pending.push(b);
+                    self.emit(v, Sequence::Ss3);
+                }
+            },
+            // strings end with ST, osc strings may end with BEL too
+            State::Osc | State::Dcs => match b {
+                7 if self.state == State::Osc => self.emit(v, Sequence::Osc),
+                b'\\' if self.pending.last() == Some(&27) => {
+                    self.pending.pop();
+                    match self.state {
+                        State::Osc => self.emit(v, Sequence::Osc),
+                        _ => self.emit(v, Sequence::Dcs),
+                    }
+                }
+                // terminals never put control bytes in their strings,
+                // so the string was alt + ] or alt + P followed by other keys
+                0..=26 | 28..=31 => self.abort_string(b, v),
+                _ => self.pending.push(b),
+            },
+            State::Paste => {
+                self.pending.push(b);
+                if self.pending.ends_with(PASTE_END) {
+                    self.pending.truncate(self.pending.len() - PASTE_END.len());
+                    self.emit(v, Sequence::Paste);
+                }
+            }
+        }
+    }
+
+    // gives back the bytes of a string that turned out to be keys
+    fn abort_string(&mut self, b: u8, v: &mut Vec<Sequence>) {
+        let intro = match self.state {
+            State::Osc => b']',
+            _ => b'P',
+        };
+        let body = std::mem::take(&mut self.pending);
+        self.reset();
+
+        v.push(Sequence::Alt(vec![27, intro]));
+        for b in body.into_iter().chain([b]) {
+            self.advance(b, v);
+        }
+    }
+
+    // starts a utf8 char, stray continuation bytes and invalid lead bytes are given back as invalid
+    fn utf8(&mut self, b: u8, v: &mut Vec<Sequence>) {
+        self.pending.push(b);
+        match b {
+            0xc0..=0xdf => self.state = State::Utf8(1),
+            0xe0..=0xef => self.state = State::Utf8(2),
+            0xf0..=0xf7 => self.state = State::Utf8(3),
+            _ => self.emit(v, Sequence::Invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod parser {
+    use super::{Parser, Sequence};
+
+    #[test]
+    fn one_read() {
+        let mut parser = Parser::new();
+        let seqs = parser.parse(b"a\x1b[A\x1b[M !!\xd8\xb7\x1bb\x1bOP\x1b[105;5u\r");
+
+        assert_eq!(
+            seqs,
+            vec![
+                Sequence::Char(b"a".to_vec()),
+                Sequence::Csi(b"\x1b[A".to_vec()),
+                Sequence::Mouse(b"\x1b[M !!".to_vec()),
+                Sequence::Char("ط".as_bytes().to_vec()),
+                Sequence::Alt(b"\x1bb".to_vec()),
+                Sequence::Ss3(b"\x1bOP".to_vec()),
+                Sequence::Csi(b"\x1b[105;5u".to_vec()),
+                Sequence::Char(b"\r".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_reads() {
+        let mut parser = Parser::new();
+        let bytes = "\x1b[1;5A\x1b[M !!é\x1b]52;c;YQ==\x1b\\\x1bP>|xterm\x1b\\".as_bytes();
+
+        // every split point, including the ones inside sequences
+        let mut seqs = vec![];
+        for b in bytes {
+            seqs.extend(parser.parse(&[*b]));
+        }
+
+        assert_eq!(
+            seqs,
+            vec![
+                Sequence::Csi(b"\x1b[1;5A".to_vec()),
+                Sequence::Mouse(b"\x1b[M !!".to_vec()),
+                Sequence::Char("é".as_bytes().to_vec()),
+                Sequence::Osc(b"52;c;YQ==".to_vec()),
+                Sequence::Dcs(b">|xterm".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn esc() {
+        let mut parser = Parser::new();
+        assert!(parser.parse(b"\x1b").is_empty());
+        assert!(parser.is_esc());
+        assert_eq!(parser.flush_esc(), Some(Sequence::Esc));
+
+        assert_eq!(
+            parser.parse(b"\x1b\x1b[B"),
+            vec![Sequence::Esc, Sequence::Csi(b"\x1b[B".to_vec())]
+        );
+        assert_eq!(parser.flush_esc(), None);
+    }
+
+    #[test]
+    fn alt_string_introducers() {
+        let mut parser = Parser::new();
+        assert!(parser.parse(b"\x1b]").is_empty());
+        assert!(parser.is_esc());
+        assert_eq!(parser.flush_esc(), Some(Sequence::Alt(b"\x1b]".to_vec())));
+        assert_eq!(parser.parse(b"a"), vec![Sequence::Char(b"a".to_vec())]);
+
+        assert!(parser.parse(b"\x1b[").is_empty());
+        assert_eq!(parser.flush_esc(), Some(Sequence::Alt(b"\x1b[".to_vec())));
+        assert!(parser.parse(b"\x1bO").is_empty());
+        assert_eq!(parser.flush_esc(), Some(Sequence::Alt(b"\x1bO".to_vec())));
+        // a control sequence that has started is not an alt key anymore
+        assert!(parser.parse(b"\x1b[1").is_empty());
+        assert_eq!(parser.flush_esc(), None);
+        assert_eq!(parser.parse(b"A"), vec![Sequence::Csi(b"\x1b[1A".to_vec())]);
+
+        // keys that came in the same read are given back once a control byte shows up
+        assert!(parser.parse(b"\x1bPab").is_empty());
+        assert!(!parser.is_esc());
+        assert_eq!(
+            parser.parse(b"\r"),
+            vec![
+                Sequence::Alt(b"\x1bP".to_vec()),
+                Sequence::Char(b"a".to_vec()),
+                Sequence::Char(b"b".to_vec()),
+                Sequence::Char(b"\r".to_vec()),
+            ]
+        );
+
+        // alt + O followed by an esc that starts the next one, then enter
+        assert_eq!(
+            parser.parse(b"\x1bO\x1bO\r"),
+            vec![
+                Sequence::Alt(b"\x1bO".to_vec()),
+                Sequence::Alt(b"\x1bO".to_vec()),
+                Sequence::Char(b"\r".to_vec()),
+            ]
+        );
+        assert!(!parser.is_esc());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut parser = Parser::new();
+        assert_eq!(
+            parser.parse(b"\xff"),
+            vec![Sequence::Invalid(b"\xff".to_vec())]
+        );
+        assert_eq!(
+            parser.parse(b"\xbf\x1b\xff"),
+            vec![
+                Sequence::Invalid(b"\xbf".to_vec()),
+                Sequence::Invalid(b"\x1b\xff".to_vec()),
+            ]
+        );
+        // a cut short char, the byte that cut it is kept
+        assert_eq!(
+            parser.parse(b"\xe2\x82a"),
+            vec![
+                Sequence::Invalid(b"\xe2\x82".to_vec()),
+                Sequence::Char(b"a".to_vec()),
+            ]
+        );
+        // alt + [ then enter
+        assert_eq!(
+            parser.parse(b"\x1b[\r"),
+            vec![
+                Sequence::Alt(b"\x1b[".to_vec()),
+                Sequence::Char(b"\r".to_vec()),
+            ]
+        );
+        assert_eq!(
+            parser.parse(b"\x1b[1;\r"),
+            vec![
+                Sequence::Invalid(b"\x1b[1;".to_vec()),
+                Sequence::Char(b"\r".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn paste() {
+        let mut parser = Parser::new();
+        assert!(parser.parse(b"\x1b[200~a\x1b[A").is_empty());
+        assert!(parser.is_pasting());
+        assert_eq!(
+            parser.parse(b"\x1b[201~b"),
+            vec![
+                Sequence::Paste(b"a\x1b[A".to_vec()),
+                Sequence::Char(b"b".to_vec())
+            ]
+        );
+    }
+}
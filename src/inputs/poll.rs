@@ -0,0 +1,80 @@
+use std::io;
+use std::os::raw::c_ulong;
+use std::time::Duration;
+
+// from /usr/include/poll.h
+extern "C" {
+    fn poll(__fds: *mut pollfd, __nfds: c_ulong, __timeout: i32) -> i32;
+}
+
+/// ffi bindings for the c struct found in '/usr/include/asm-generic/poll.h'
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+struct pollfd {
+    fd: i32,
+    events: i16,
+    revents: i16,
+}
+
+// from /usr/include/asm-generic/poll.h
+// there is data to read
+const POLLIN: i16 = 0x001;
+// error condition, only ever returned in revents
+const POLLERR: i16 = 0x008;
+// hung up, only ever returned in revents
+const POLLHUP: i16 = 0x010;
+// the fd is not open, only ever returned in revents
+const POLLNVAL: i16 = 0x020;
+
+// from /usr/include/asm-generic/errno-base.h
+const EBADF: i32 = 9;
+
+/// waits until at least one of the given file descriptors has data to read
+/// or until the timeout runs out
+/// a timeout of None waits forever, a zero timeout returns immediately
+/// returns the file descriptors that are ready to be read,
+/// which is an empty vec if the timeout ran out first or if the wait got interrupted by a signal
+/// a file descriptor that hung up or has an error pending is ready too,
+/// reading it then gives the end of file or the error
+///
+/// # Errors
+/// returns an error if one of the file descriptors is not open, or if poll itself fails
+pub fn poll_fds(fds: &[i32], timeout: Option<Duration>) -> io::Result<Vec<i32>> {
+    let mut pfds = fds
+        .iter()
+        .map(|fd| pollfd {
+            fd: *fd,
+            events: POLLIN,
+            revents: 0,
+        })
+        .collect::<Vec<pollfd>>();
+
+    let timeout = match timeout {
+        None => -1,
+        // round up so that a 0.5ms timeout doesn't become a busy loop
+        Some(t) => t.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32,
+    };
+
+    let res = unsafe { poll(pfds.as_mut_ptr(), pfds.len() as c_ulong, timeout) };
+    if res == -1 {
+        let e = io::Error::last_os_error();
+        return match e.kind() {
+            io::ErrorKind::Interrupted => Ok(vec![]),
+            _ => Err(e),
+        };
+    }
+    // the timeout ran out
+    if res == 0 {
+        return Ok(vec![]);
+    }
+
+    if pfds.iter().any(|p| p.revents & POLLNVAL != 0) {
+        return Err(io::Error::from_raw_os_error(EBADF));
+    }
+
+    Ok(pfds
+        .into_iter()
+        .filter(|p| p.revents & (POLLIN | POLLERR | POLLHUP) != 0)
+        .map(|p| p.fd)
+        .collect())
+}
@@ -1,10 +1,22 @@
+use std::io::Write;
+
+use crate::console::signals::{self, SIGWINCH};
 use crate::console::winsize::winsize;
 
 #[derive(Debug)]
 pub enum WindowEvent {
-    WindowResized,
-    // WindowGainedFocus,
-    // WindowLostFocus,
+    /// the terminal window was resized
+    /// carries the new width and height of the window
+    WindowResized { cols: u16, rows: u16 },
+    /// the program was continued after being suspended or stopped
+    /// the screen content is gone and has to be rendered again in full
+    WindowResumed,
+    /// the terminal window gained focus
+    /// requires focus input to be enabled
+    WindowGainedFocus,
+    /// the terminal window lost focus
+    /// requires focus input to be enabled
+    WindowLostFocus,
     // WindowMaximized,
     // WindowMinimized,
     // WindowClosed,
@@ -12,45 +24,32 @@ pub enum WindowEvent {
     // WindowWindowed,
 }
 
-pub struct WindowResized;
+/// starts listening to window resizes
+/// installs a SIGWINCH handler that writes to the console signals pipe
+/// returns the fd to poll for resize notifications
+/// calling this more than once is harmless
+pub fn listen_resize() -> Option<i32> {
+    signals::listen(SIGWINCH)
+}
 
-use crate::components::Term;
+/// makes a WindowResized event out of the current window size of the terminal behind the given fd
+pub fn window_resized(fd: i32) -> WindowEvent {
+    let ws = winsize::from_fd(fd);
 
-// resolve linux display/compositor protocol
-// returns 0 for wayland or 1 for x11
-#[cfg(target_os = "linux")]
-fn compositor() -> u8 {
-    let is_wayland = std::process::Command::new("printenv")
-        .arg("WAYLAND_DISPLAY")
-        .output();
-    // if err then no wayland
-    if is_wayland.is_err() {
-        1
-    } else {
-        0
+    WindowEvent::WindowResized {
+        cols: ws.cols(),
+        rows: ws.rows(),
     }
 }
 
-fn window_resized() /* -> WindowResized */
-{
-    #[cfg(target_os = "linux")]
-    match compositor() {
-        0 => {
-            // wayland
-        }
-        1 => {
-            // x11
-        }
-        _ => unreachable!(),
-    }
+/// enables focus input reporting
+/// the terminal then reports when its window gains or loses focus,
+/// which come as WindowGainedFocus and WindowLostFocus events
+pub fn enable_focus_input(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?1004h");
+}
 
-    #[cfg(target_os = "windows")]
-    // windows get window resize values
-    #[cfg(target_os = "macos")]
-    // macos get window resize values
-    if cfg!(x86_64) {
-        // amd64 macos
-    } else if cfg!(aarch64) {
-        // apple silicon aarch64 macos
-    }
+/// disables focus input reporting
+pub fn disable_focus_input(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[?1004l");
 }
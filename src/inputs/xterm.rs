@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use super::keyboard::{KbdEvent, Modifiers};
+use super::kitty::unicode_key;
+
+/// turns xterm's modifyOtherKeys on at level 2
+/// keys with modifiers that have no legacy encoding, e.g., ctrl+shift+a or ctrl+.,
+/// then come as CSI 27 ; modifiers ; code ~ sequences
+/// terminals without it ignore this and keep sending legacy sequences
+pub fn enable_modify_other_keys(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[>4;2m");
+}
+
+/// resets modifyOtherKeys to the terminal's default
+pub fn disable_modify_other_keys(writer: &mut impl Write) {
+    _ = writer.write(b"\x1b[>4m");
+}
+
+/// decodes a single modifyOtherKeys sequence into a KbdEvent
+/// CSI 27 ; modifiers ; code ~
+/// returns None if the sequence is malformed or the key is not one this crate knows of
+pub fn decode(seq: &[u8]) -> Option<KbdEvent> {
+    let body = seq.strip_prefix(b"\x1b[27;")?.strip_suffix(b"~")?;
+    let mut params = std::str::from_utf8(body).ok()?.split(';');
+    let modifiers = params.next()?.parse().ok()?;
+    let code = params.next()?.parse().ok()?;
+    if params.next().is_some() {
+        return None;
+    }
+
+    Some(KbdEvent {
+        char: unicode_key(code)?,
+        modifiers: Modifiers::from_param(modifiers),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod xterm {
+    use super::decode;
+    use crate::inputs::keyboard::{Char, Modifiers, CC};
+
+    #[test]
+    fn keys() {
+        [
+            (&b"\x1b[27;5;105~"[..], Char::Char('i'), 2),
+            (b"\x1b[27;6;65~", Char::Char('A'), 10),
+            (b"\x1b[27;5;46~", Char::Char('.'), 2),
+            (b"\x1b[27;2;13~", Char::CC(CC::CR), 8),
+            (b"\x1b[27;3;9~", Char::CC(CC::TAB), 4),
+        ]
+        .into_iter()
+        .for_each(|(seq, char, modifiers)| {
+            let ke = decode(seq).unwrap();
+            assert_eq!(ke.char, char, "{:?}", seq);
+            assert_eq!(ke.modifiers, Modifiers(modifiers), "{:?}", seq);
+        });
+
+        assert!(decode(b"\x1b[27;5~").is_none());
+    }
+}
@@ -5,6 +5,8 @@
 pub mod components;
 /// console utilities; winsize and termios (raw_mode)
 pub mod console;
+/// extended grapheme clusters, the user perceived characters, and their segmentation
+pub mod grapheme;
 /// keyboard and mouse input detection and decoding
 pub mod inputs;
 pub mod overlay;
@@ -67,14 +69,12 @@ pub fn frames(fps: u64) {
 //      1.4 define you output events and components behavior
 //      1.5 init the tree
 // 2 setup your environment
-//      2.1 call raw mode
-//      2.2 call alternate screen
-//      2.3 optionally turn on raw mouse inputs
-//      2.4 call events to read input events
-//      2.5 init you terminal reader and writer
+//      2.1 init you terminal reader and writer
+//      2.2 make a console Session
+//          2.2.1 call raw mode
+//          2.2.2 call alternate screen
+//          2.2.3 optionally turn on raw mouse inputs
+//      2.3 call events to read input events
 // 3 write your event loop
 //      3.1 write your event loop logic
-//      3.2 don't forget to clean up before leaving the program
-//          3.2.1 call cooked mode
-//          3.2.2 leave alternate screen
-//          3.3.3 disable raw mouse inputs in case you enabled it
+//      3.2 the Session cleans up when dropped, even on panic
@@ -1,12 +1,15 @@
 use std::collections::HashMap;
-use std::io::{StdoutLock, Write};
+use std::io::Write;
 use std::str::Chars;
 
 use crate::components::*;
+use crate::grapheme::Grapheme;
 use crate::space::{border::Border, padding::Padding};
 use crate::themes::Style;
 
 pub mod container;
+pub mod headless;
+pub mod inline;
 pub mod term;
 pub mod text;
 
@@ -16,6 +19,9 @@ pub mod text;
 
 // NOTE: the render methods here depend on the value field of text objects having a text.w * text.h  len
 
+// NOTE: every render method writes to a generic 'impl Write' sink
+// so the same tree can be rendered to stdout, /dev/tty, a file, a socket or an in memory Vec<u8>
+
 // TODO: should process only if values change
 // otherwise just render
 
@@ -56,12 +62,25 @@ pub(crate) fn spread_padding(p: &Padding) -> [u16; 8] {
     }
 }
 
-fn log_buf(buf: &[Option<char>], w: u16, h: u16) {
+// a prepared cell: the grapheme to draw, None if the cell is left as it is,
+// and the hyperlink the cell is part of, if any
+pub(crate) type Cell<'a> = (Option<Grapheme>, Option<&'a Hyperlink>);
+
+// opens the given hyperlink, or closes the open one if None
+// opening a link implicitly closes the previous one
+pub(crate) fn osc8(link: Option<&Hyperlink>) -> String {
+    match link {
+        Some(link) => format!("\x1b]8;id={};{}\x1b\\", link.id, link.uri),
+        None => "\x1b]8;;\x1b\\".to_string(),
+    }
+}
+
+fn log_buf(buf: &[Cell], w: u16, h: u16) {
     print!("lines");
     for ih in 0..h {
         println!("");
         for iw in 0..w {
-            print!("{}", (buf[(iw + ih * w) as usize]).unwrap_or(' '));
+            print!("{}", (buf[(iw + ih * w) as usize].0).unwrap_or(' '.into()));
         }
     }
     println!("");
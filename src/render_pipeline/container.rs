@@ -1,22 +1,23 @@
 use std::collections::HashMap;
-use std::io::{StdoutLock, Write};
+use std::io::Write;
 use std::str::Chars;
 
 use crate::components::*;
+use crate::grapheme::Grapheme;
 use crate::space::{border::Border, padding::Padding};
 use crate::themes::Style;
 
-use super::spread_padding;
+use super::{spread_padding, Cell};
 
 impl Container {
     /// wrapper around the render_border and render_value method calls
-    pub fn render(&self, writer: &mut StdoutLock) {
+    pub fn render(&self, writer: &mut impl Write) {
         self.render_border(writer);
         self.render_value(writer);
     }
 
     /// renders only the items inside the container
-    pub fn render_value(&self, writer: &mut StdoutLock) {
+    pub fn render_value(&self, writer: &mut impl Write) {
         let [_, pol, pot, _, _, pil, pit, _] = spread_padding(&self.padding);
         let cb = if let Border::None = self.border { 0 } else { 1 };
 
@@ -34,7 +35,7 @@ impl Container {
     }
 
     /// renders only the container border
-    pub fn render_border(&self, writer: &mut StdoutLock) {
+    pub fn render_border(&self, writer: &mut impl Write) {
         let [_, pol, pot, _, pir, pil, pit, pib] = spread_padding(&self.padding);
         let [xb, yb] = [self.x0 + pol + 1, self.y0 + pot];
         let mut s = format!("{}\x1b[{};{}f", &self.bstyle, yb, xb);
@@ -112,7 +113,7 @@ impl Container {
 
     // prepares the border and paddings of the container
     // then calls all the self items prepare methods
-    pub(super) fn prepare(&self) -> (Vec<Option<char>>, [u16; 2]) {
+    pub(super) fn prepare(&self) -> (Vec<Cell<'_>>, [u16; 2]) {
         // make out each line of the item, padding and border included
         // then render line
         // until all lines are rendered
@@ -122,12 +123,12 @@ impl Container {
             _ => 1,
         };
 
-        let mut lines: Vec<Option<char>> = vec![];
+        let mut lines: Vec<Cell> = vec![];
 
         // wx is the number of chars in a line
         // hx is the number of lines
         let [wx, mut hx] = self.decorate();
-        lines.resize((wx * hx) as usize, None);
+        lines.resize((wx * hx) as usize, (None, None));
 
         self.process(&mut lines);
 
@@ -140,7 +141,7 @@ impl Container {
                 // write the item line inside the container lines
                 for tidx in 0..twx {
                     let cell = cells[(tidx + line * twx) as usize];
-                    if cell.is_some() {
+                    if cell.0.is_some() {
                         lines[idx as usize] = cell;
                     }
                     idx += 1;
@@ -162,7 +163,7 @@ impl Container {
         (lines, [wx, hx])
     }
 
-    fn process(&self, lines: &mut Vec<Option<char>>) {
+    fn process(&self, lines: &mut Vec<Cell<'_>>) {
         let [por, pol, pot, pob, pir, pil, pit, pib] = spread_padding(&self.padding);
 
         let [wx, hx] = self.decorate();
@@ -216,7 +217,7 @@ impl Container {
 
     fn process_none(
         &self,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'_>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -252,7 +253,7 @@ impl Container {
     fn process_uniform(
         &self,
         c: char,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'_>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -272,7 +273,7 @@ impl Container {
         idx += pol;
         // we fill value length + inner padding right + left with border value
         for i in 0..pil + 1 + self.w + pir + 1 {
-            lines[idx as usize] = Some(c);
+            lines[idx as usize] = (Some(c.into()), None);
             idx += 1;
         }
         // we skipp the outer right padding
@@ -287,12 +288,12 @@ impl Container {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = Some(c);
+            lines[idx as usize] = (Some(c.into()), None);
             idx += 1;
             // skip inner left and right padding and the value width
             idx += pil + self.w + pir;
             // border cell
-            lines[idx as usize] = Some(c);
+            lines[idx as usize] = (Some(c.into()), None);
             idx += 1;
             // skipp outer right padding
             idx += por;
@@ -306,7 +307,7 @@ impl Container {
         idx += pol;
         // we fill value length + inner padding right + left with border value
         for i in 0..pil + 1 + self.w + pir + 1 {
-            lines[idx as usize] = Some(c);
+            lines[idx as usize] = (Some(c.into()), None);
             idx += 1;
         }
         // we skip the outer right padding
@@ -324,7 +325,7 @@ impl Container {
         brcorner: char,
         btb: char,
         blr: char,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'_>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -344,18 +345,18 @@ impl Container {
         idx += pol;
 
         // we write the top left corner
-        lines[idx as usize] = Some(tlcorner);
+        lines[idx as usize] = (Some(tlcorner.into()), None);
         idx += 1;
 
         // we fill value length + inner padding right + left with border top/bottom value,
         // excluding the top corners
         for i in 0..pil + self.w + pir {
-            lines[idx as usize] = Some(btb);
+            lines[idx as usize] = (Some(btb.into()), None);
             idx += 1;
         }
 
         // we write the top right corner
-        lines[idx as usize] = Some(trcorner);
+        lines[idx as usize] = (Some(trcorner.into()), None);
         idx += 1;
 
         // we skipp the outer right padding
@@ -370,12 +371,12 @@ impl Container {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip inner left and right padding and the value width
             idx += pil + self.w + pir;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skipp outer right padding
             idx += por;
@@ -388,15 +389,15 @@ impl Container {
         // we skip the outer left padding values
         idx += pol;
         // we write the border bottom left corner value
-        lines[idx as usize] = Some(blcorner);
+        lines[idx as usize] = (Some(blcorner.into()), None);
         idx += 1;
         // we fill value length + inner padding right + left with border value
         for i in 0..pil + self.w + pir {
-            lines[idx as usize] = Some(btb);
+            lines[idx as usize] = (Some(btb.into()), None);
             idx += 1;
         }
         // we write the border bottom right value
-        lines[idx as usize] = Some(brcorner);
+        lines[idx as usize] = (Some(brcorner.into()), None);
         idx += 1;
         // we skip the outer right padding
         idx += por;
@@ -423,7 +424,7 @@ impl Container {
         b0: &str,
         bp: char,
         b1: &str,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'_>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -516,19 +517,19 @@ impl Container {
         idx += pol;
 
         // we write the top left corner
-        lines[idx as usize] = Some(tlcorner);
+        lines[idx as usize] = (Some(tlcorner.into()), None);
         idx += 1;
 
         // log_buf(&lines, wx, hx);
 
         // write top border values
         while let Some(ch) = bt.next() {
-            lines[idx as usize] = Some(ch);
+            lines[idx as usize] = (Some(ch.into()), None);
             idx += 1;
         }
 
         // we write the top right corner
-        lines[idx as usize] = Some(trcorner);
+        lines[idx as usize] = (Some(trcorner.into()), None);
         idx += 1;
 
         // println!("lines ==> {:?}", lines);
@@ -546,12 +547,12 @@ impl Container {
             // new line we skip padding outer left
             idx += pol;
             // left border cell
-            lines[idx as usize] = bl.next();
+            lines[idx as usize] = (bl.next().map(Grapheme::from), None);
             idx += 1;
             // skip inner left and right padding and the value width
             idx += pil + self.w + pir;
             // right border cell
-            lines[idx as usize] = br.next();
+            lines[idx as usize] = (br.next().map(Grapheme::from), None);
             idx += 1;
             // skipp outer right padding
             idx += por;
@@ -564,17 +565,17 @@ impl Container {
         idx += pol;
 
         // we write the border bottom left corner value
-        lines[idx as usize] = Some(blcorner);
+        lines[idx as usize] = (Some(blcorner.into()), None);
         idx += 1;
 
         // write bottom border values
         while let Some(ch) = bb.next() {
-            lines[idx as usize] = Some(ch);
+            lines[idx as usize] = (Some(ch.into()), None);
             idx += 1;
         }
 
         // we write the border bottom right value
-        lines[idx as usize] = Some(brcorner);
+        lines[idx as usize] = (Some(brcorner.into()), None);
         idx += 1;
 
         // println!("{}: idx = {}, line = {}", line!(), idx, line,);
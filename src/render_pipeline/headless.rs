@@ -0,0 +1,600 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::grapheme::Grapheme;
+
+// the effect bits are the same ones themes::Style uses
+const BOLD: u8 = 1;
+const FAINT: u8 = 2;
+const ITALIC: u8 = 4;
+const UNDERLINE: u8 = 8;
+const BLINK: u8 = 16;
+const REVERSE: u8 = 32;
+const CONCEAL: u8 = 64;
+const DBL_UNDERLINE: u8 = 128;
+
+/// the environment variable that makes assert_golden (re)write its golden files
+/// instead of comparing against them
+pub const BLESS: &str = "BARARAQ_BLESS";
+
+/// the graphic rendition of a single cell
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CellStyle {
+    /// the effect bits, same values as the themes::Style effects
+    pub effects: u8,
+    /// the rgb text color, None is the terminal default
+    pub text: Option<[u8; 3]>,
+    /// the rgb background color, None is the terminal default
+    pub background: Option<[u8; 3]>,
+}
+
+impl CellStyle {
+    /// whether this style has the bold effect
+    pub fn is_bold(&self) -> bool {
+        self.effects & BOLD != 0
+    }
+
+    /// whether this style has the faint effect
+    pub fn is_faint(&self) -> bool {
+        self.effects & FAINT != 0
+    }
+
+    /// whether this style has the italic effect
+    pub fn is_italic(&self) -> bool {
+        self.effects & ITALIC != 0
+    }
+
+    /// whether this style has the underline effect
+    pub fn is_underline(&self) -> bool {
+        self.effects & UNDERLINE != 0
+    }
+
+    /// whether this style has the reverse effect
+    pub fn is_reverse(&self) -> bool {
+        self.effects & REVERSE != 0
+    }
+
+    // applies a select graphic rendition parameter list
+    fn sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::default();
+            return;
+        }
+
+        let mut iter = params.iter();
+        while let Some(p) = iter.next() {
+            match p {
+                0 => *self = Self::default(),
+                1 => self.effects |= BOLD,
+                2 => self.effects |= FAINT,
+                3 => self.effects |= ITALIC,
+                4 => self.effects |= UNDERLINE,
+                5 | 6 => self.effects |= BLINK,
+                7 => self.effects |= REVERSE,
+                8 => self.effects |= CONCEAL,
+                21 => self.effects |= DBL_UNDERLINE,
+                22 => self.effects &= !(BOLD | FAINT),
+                23 => self.effects &= !ITALIC,
+                24 => self.effects &= !(UNDERLINE | DBL_UNDERLINE),
+                25 => self.effects &= !BLINK,
+                27 => self.effects &= !REVERSE,
+                28 => self.effects &= !CONCEAL,
+                38 => self.text = Self::color(&mut iter),
+                39 => self.text = None,
+                48 => self.background = Self::color(&mut iter),
+                49 => self.background = None,
+                // the 16 color palette is not something bararaq emits
+                _ => (),
+            }
+        }
+    }
+
+    // reads the rest of a 38 or 48 parameter
+    // only the rgb form is kept, the indexed form is consumed and dropped
+    fn color<'a>(iter: &mut impl Iterator<Item = &'a u16>) -> Option<[u8; 3]> {
+        match iter.next() {
+            Some(2) => {
+                let mut rgb = [0; 3];
+                rgb.iter_mut()
+                    .for_each(|c| *c = iter.next().copied().unwrap_or(0) as u8);
+
+                Some(rgb)
+            }
+            Some(5) => {
+                iter.next();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// a single cell of the headless grid
+/// holds a whole grapheme cluster, e.g., a letter with its combining marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub char: Grapheme,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            char: ' '.into(),
+            style: CellStyle::default(),
+        }
+    }
+}
+
+/// an in memory terminal to render into
+/// interprets the bytes that the render_pipeline writes into a grid of cells,
+/// so that what ends up on screen can be asserted on without a real terminal
+///
+/// understood sequences:
+/// cursor moves (CSI H, f, A, B, C, D, G, d, s, u and ESC 7, ESC 8),
+/// erases (CSI J, K, X), graphic rendition (CSI m) and the \r, \n, \x08 controls
+/// anything else, e.g. private modes or OSC strings, is consumed and ignored
+/// chars that continue a grapheme cluster, e.g. combining marks, join the cell before them
+///
+/// # Examples
+/// ```
+/// use std::io::Write;
+/// use bararaq::render_pipeline::headless::Headless;
+///
+/// let mut screen = Headless::new(8, 2);
+/// write!(screen, "\x1b[2;3fhi").unwrap();
+///
+/// screen.assert_snapshot(
+///     "
+///
+///   hi",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Headless {
+    w: u16,
+    h: u16,
+    cells: Vec<Cell>,
+    // 0 based cursor position
+    cx: u16,
+    cy: u16,
+    // set when a char was written to the last column,
+    // the next char then goes to the start of the next line
+    wrap: bool,
+    style: CellStyle,
+    saved: (u16, u16, CellStyle),
+    // bytes of an unfinished sequence or char, kept until the next write
+    pending: Vec<u8>,
+}
+
+impl Headless {
+    /// creates a new blank grid of the given width and height
+    pub fn new(w: u16, h: u16) -> Self {
+        Self {
+            w,
+            h,
+            cells: vec![Cell::default(); w as usize * h as usize],
+            cx: 0,
+            cy: 0,
+            wrap: false,
+            style: CellStyle::default(),
+            saved: (0, 0, CellStyle::default()),
+            pending: vec![],
+        }
+    }
+
+    /// the width and height of the grid
+    pub fn size(&self) -> [u16; 2] {
+        [self.w, self.h]
+    }
+
+    /// the 1 based cursor position, in the same [x, y] order the components use
+    pub fn cursor(&self) -> [u16; 2] {
+        [self.cx + 1, self.cy + 1]
+    }
+
+    /// returns the cell at the given 0 based position
+    pub fn cell(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[(x + y * self.w) as usize]
+    }
+
+    /// returns the text of every line of the grid, trailing blanks included
+    pub fn lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.w.max(1) as usize)
+            .map(|line| line.iter().map(|c| c.char.as_str()).collect())
+            .collect()
+    }
+
+    /// returns the grid text as it is compared by the snapshot assertions
+    /// trailing blanks of every line and trailing blank lines are dropped
+    pub fn snapshot(&self) -> String {
+        normalize(&self.lines().join("\n"))
+    }
+
+    /// asserts that the grid text matches the expected one
+    /// the expected text may start with a newline, so that it can be written as an indented literal
+    /// trailing blanks of every line and trailing blank lines are ignored on both sides
+    pub fn assert_snapshot(&self, expected: &str) {
+        let expected = normalize(expected.strip_prefix('\n').unwrap_or(expected));
+        let actual = self.snapshot();
+
+        assert!(
+            actual == expected,
+            "headless snapshot mismatch\n--- expected\n{}\n--- actual\n{}\n---",
+            expected,
+            actual
+        );
+    }
+
+    /// asserts that the grid text matches the content of a golden file
+    /// when the BARARAQ_BLESS environment variable is set the file is written instead,
+    /// which is how golden files get created and updated
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.snapshot();
+
+        if std::env::var_os(BLESS).is_some() {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).unwrap();
+            }
+            std::fs::write(path, actual + "\n").unwrap();
+            return;
+        }
+
+        let expected = match std::fs::read_to_string(path) {
+            Ok(s) => normalize(&s),
+            Err(e) => panic!(
+                "could not read golden file {}: {}\nrun with {}=1 to create it",
+                path.display(),
+                e,
+                BLESS
+            ),
+        };
+
+        assert!(
+            actual == expected,
+            "golden file {} mismatch\n--- expected\n{}\n--- actual\n{}\n---\nrun with {}=1 to update it",
+            path.display(),
+            expected,
+            actual,
+            BLESS
+        );
+    }
+
+    // blanks the cells in the given index range with the current background
+    fn erase(&mut self, from: usize, to: usize) {
+        let blank = Cell {
+            char: ' '.into(),
+            style: CellStyle {
+                background: self.style.background,
+                ..CellStyle::default()
+            },
+        };
+        let to = to.min(self.cells.len());
+        if from < to {
+            self.cells[from..to].fill(blank);
+        }
+    }
+
+    fn scroll_up(&mut self) {
+        let w = self.w as usize;
+        self.cells.drain(..w);
+        self.cells.resize(self.cells.len() + w, Cell::default());
+    }
+
+    fn line_feed(&mut self) {
+        if self.cy + 1 < self.h {
+            self.cy += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if self.w == 0 || self.h == 0 {
+            return;
+        }
+        // a char that continues the cluster left of the cursor joins its cell,
+        // the same way terminals put combining marks on the previous char
+        let prev = match (self.wrap, self.cx) {
+            (true, _) => Some(self.cx),
+            (false, 0) => None,
+            (false, x) => Some(x - 1),
+        };
+        if let Some(x) = prev {
+            let idx = (x + self.cy * self.w) as usize;
+            if self.cells[idx].char.push(c) {
+                return;
+            }
+        }
+
+        if self.wrap {
+            self.wrap = false;
+            self.cx = 0;
+            self.line_feed();
+        }
+
+        let idx = (self.cx + self.cy * self.w) as usize;
+        self.cells[idx] = Cell {
+            char: c.into(),
+            style: self.style,
+        };
+
+        if self.cx + 1 < self.w {
+            self.cx += 1;
+        } else {
+            self.wrap = true;
+        }
+    }
+
+    fn goto(&mut self, x: u16, y: u16) {
+        self.cx = x.min(self.w.saturating_sub(1));
+        self.cy = y.min(self.h.saturating_sub(1));
+        self.wrap = false;
+    }
+
+    fn csi(&mut self, private: bool, params: &[u16], fin: u8) {
+        // private mode sequences, e.g. \x1b[?1049h, do not touch the grid
+        if private {
+            return;
+        }
+
+        // most sequences treat a missing or 0 parameter as 1
+        let n = |idx: usize| params.get(idx).copied().unwrap_or(0).max(1);
+        let (cx, cy) = (self.cx, self.cy);
+        let w = self.w as usize;
+        let cursor = cx as usize + cy as usize * w;
+
+        match fin {
+            b'H' | b'f' => self.goto(n(1) - 1, n(0) - 1),
+            b'A' => self.goto(cx, cy.saturating_sub(n(0))),
+            b'B' => self.goto(cx, cy.saturating_add(n(0))),
+            b'C' => self.goto(cx.saturating_add(n(0)), cy),
+            b'D' => self.goto(cx.saturating_sub(n(0)), cy),
+            b'G' => self.goto(n(0) - 1, cy),
+            b'd' => self.goto(cx, n(0) - 1),
+            b'J' => match params.first().copied().unwrap_or(0) {
+                0 => self.erase(cursor, self.cells.len()),
+                1 => self.erase(0, cursor + 1),
+                _ => self.erase(0, self.cells.len()),
+            },
+            b'K' => {
+                let line = cy as usize * w;
+                match params.first().copied().unwrap_or(0) {
+                    0 => self.erase(cursor, line + w),
+                    1 => self.erase(line, cursor + 1),
+                    _ => self.erase(line, line + w),
+                }
+            }
+            b'X' => self.erase(cursor, (cursor + n(0) as usize).min(cy as usize * w + w)),
+            b'm' => self.style.sgr(params),
+            b's' => self.saved = (cx, cy, self.style),
+            b'u' => {
+                let (x, y, _) = self.saved;
+                self.goto(x, y);
+            }
+            _ => (),
+        }
+    }
+
+    // interprets as many bytes as it can
+    // returns the number of consumed bytes, anything after that is an unfinished sequence
+    fn process(&mut self, bytes: &[u8]) -> usize {
+        let mut i = 0;
+        while i < bytes.len() {
+            let consumed = match bytes[i] {
+                b'\x1b' => match self.escape(&bytes[i..]) {
+                    Some(n) => n,
+                    None => return i,
+                },
+                b'\r' => {
+                    self.goto(0, self.cy);
+                    1
+                }
+                b'\n' => {
+                    self.wrap = false;
+                    self.line_feed();
+                    1
+                }
+                b'\x08' => {
+                    self.goto(self.cx.saturating_sub(1), self.cy);
+                    1
+                }
+                b if b < 0x20 || b == 0x7f => 1,
+                _ => match char_len(&bytes[i..]) {
+                    Some(len) => {
+                        let s = std::str::from_utf8(&bytes[i..i + len]);
+                        self.put(s.map_or('\u{fffd}', |s| s.chars().next().unwrap()));
+                        len
+                    }
+                    None => return i,
+                },
+            };
+            i += consumed;
+        }
+
+        i
+    }
+
+    // handles a sequence starting with ESC
+    // returns the sequence length, or None if the sequence is not complete yet
+    fn escape(&mut self, bytes: &[u8]) -> Option<usize> {
+        match bytes.get(1)? {
+            b'[' => {
+                let private = matches!(bytes.get(2)?, b'?' | b'>' | b'<' | b'=');
+                let start = if private { 3 } else { 2 };
+                let end = start
+                    + bytes[start..]
+                        .iter()
+                        .position(|b| (0x40..0x7f).contains(b))?;
+
+                let params = bytes[start..end]
+                    .split(|b| *b == b';')
+                    .map(|p| {
+                        p.iter().filter(|b| b.is_ascii_digit()).fold(0u16, |n, b| {
+                            n.saturating_mul(10).saturating_add((b - b'0') as u16)
+                        })
+                    })
+                    .collect::<Vec<u16>>();
+                let params = if end == start { &[][..] } else { &params[..] };
+
+                // intermediate bytes, e.g. the $ of DECRQM, make it some other sequence
+                let intermediate = bytes[start..end].iter().any(|b| (0x20..0x30).contains(b));
+                if !intermediate {
+                    self.csi(private, params, bytes[end]);
+                }
+
+                Some(end + 1)
+            }
+            // OSC, DCS, APC, PM and SOS strings end with BEL or ST
+            b']' | b'P' | b'_' | b'^' | b'X' => {
+                let mut i = 2;
+                loop {
+                    match bytes.get(i)? {
+                        b'\x07' => return Some(i + 1),
+                        b'\x1b' if *bytes.get(i + 1)? == b'\\' => return Some(i + 2),
+                        _ => i += 1,
+                    }
+                }
+            }
+            b'7' => {
+                self.saved = (self.cx, self.cy, self.style);
+                Some(2)
+            }
+            b'8' => {
+                let (x, y, style) = self.saved;
+                self.goto(x, y);
+                self.style = style;
+                Some(2)
+            }
+            // charset designations carry one more byte
+            b'(' | b')' | b'*' | b'+' => bytes.get(2).map(|_| 3),
+            _ => Some(2),
+        }
+    }
+}
+
+impl Write for Headless {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(buf);
+
+        let consumed = self.process(&bytes);
+        self.pending = bytes.split_off(consumed);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// the length of the utf-8 char that starts the given bytes
+// None if the char is not complete yet
+fn char_len(bytes: &[u8]) -> Option<usize> {
+    let len = match bytes[0] {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    };
+
+    (bytes.len() >= len).then_some(len)
+}
+
+fn normalize(s: &str) -> String {
+    let mut lines = s.lines().map(|l| l.trim_end()).collect::<Vec<&str>>();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod headless {
+    use super::{CellStyle, Headless};
+    use std::io::Write;
+
+    #[test]
+    fn cursor_moves() {
+        let mut screen = Headless::new(10, 4);
+        write!(screen, "\x1b[2;4fabc\x1b[2Dz\x1b[1Bq\x1b[4;1f\x1b[3Gend").unwrap();
+
+        screen.assert_snapshot(
+            "
+
+   azc
+     q
+  end",
+        );
+        assert_eq!(screen.cursor(), [6, 4]);
+    }
+
+    #[test]
+    fn skips_and_erases() {
+        let mut screen = Headless::new(6, 2);
+        write!(
+            screen,
+            "abcdef\x1b[1;1fx\x1b[Cy\x1b[2;1fghijkl\x1b[2;2f\x1b[3X"
+        )
+        .unwrap();
+        screen.assert_snapshot("xbydef\ng   kl");
+
+        write!(screen, "\x1b[1;4f\x1b[K\x1b[2;1f\x1b[J").unwrap();
+        screen.assert_snapshot("xby");
+    }
+
+    #[test]
+    fn styles() {
+        let mut screen = Headless::new(4, 1);
+        write!(
+            screen,
+            "\x1b[1;3;38;2;1;2;3mab\x1b[0m\x1b[48;2;9;8;7mc\x1b[4X"
+        )
+        .unwrap();
+
+        let bold = screen.cell(0, 0).style;
+        assert!(bold.is_bold() && bold.is_italic());
+        assert_eq!(bold.text, Some([1, 2, 3]));
+        assert_eq!(screen.cell(2, 0).style.background, Some([9, 8, 7]));
+        assert_eq!(screen.cell(2, 0).style.text, None);
+        // erases keep the current background
+        assert_eq!(screen.cell(3, 0).style.background, Some([9, 8, 7]));
+        assert_eq!(screen.cell(2, 0).char, 'c'.into());
+        assert_eq!(screen.cell(1, 0).style, bold);
+        assert_ne!(screen.cell(1, 0).style, CellStyle::default());
+    }
+
+    #[test]
+    fn split_writes() {
+        let mut screen = Headless::new(6, 2);
+        let bytes = "\x1b]0;title\x07\x1b[2;2fé\x1b7\x1b[1;1f\x1b8ok".as_bytes();
+        bytes.chunks(1).for_each(|b| {
+            screen.write_all(b).unwrap();
+        });
+
+        screen.assert_snapshot("\n\n éok");
+    }
+
+    #[test]
+    fn graphemes() {
+        let mut screen = Headless::new(4, 1);
+        write!(screen, "e\u{301}👨\u{200d}👩\u{200d}👧x").unwrap();
+
+        assert_eq!(screen.cell(0, 0).char.as_str(), "e\u{301}");
+        assert_eq!(screen.cell(1, 0).char.as_str(), "👨\u{200d}👩\u{200d}👧");
+        assert_eq!(screen.cell(2, 0).char.as_str(), "x");
+        assert_eq!(screen.cursor(), [4, 1]);
+    }
+
+    #[test]
+    fn wraps_and_scrolls() {
+        let mut screen = Headless::new(3, 2);
+        write!(screen, "abcdef\r\ngh").unwrap();
+
+        assert_eq!(screen.lines(), vec!["def", "gh "]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::{BufRead, Write};
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+
+use crate::components::*;
+use crate::console::cursor_position;
+use crate::console::winsize::winsize;
+use crate::grapheme::graphemes;
+
+// NOTE: the viewport origin is the window row of its first line, kept in Term.origin
+// every move into the viewport goes there with an absolute cursor move,
+// so cursor saves and restores by the program, or by anything it runs, can't lose it
+// the origin only moves when the viewport does; open_inline and insert_before
+
+// how long open_inline waits for the terminal to report the cursor position
+const CPR_TIMEOUT: Duration = Duration::from_millis(200);
+
+impl Term {
+    // moves the cursor down to make room for the viewport lines,
+    // scrolling the primary screen if the cursor is too close to the bottom,
+    // then moves it back up to the viewport's first line
+    fn reserve(&self, s: &mut String) {
+        let Viewport::Inline { h } = self.viewport else {
+            return;
+        };
+
+        s.push('\r');
+        for _ in 1..h {
+            s.push('\n');
+        }
+        if h > 1 {
+            s.push_str(&format!("\x1b[{}A", h - 1));
+        }
+    }
+
+    /// reserves the viewport lines of an inline term below the cursor
+    /// then asks the terminal where they start, reading the reply from the reader, usually stdin;
+    /// the terminal has to be in raw mode for that, see console::cursor_position
+    /// if the terminal never replies, the viewport takes the bottom lines of the window
+    /// does nothing for a fullscreen term
+    pub fn open_inline(&mut self, reader: &mut (impl BufRead + AsRawFd), writer: &mut impl Write) {
+        let Viewport::Inline { h } = self.viewport else {
+            return;
+        };
+
+        let mut s = String::new();
+        self.reserve(&mut s);
+        _ = writer.write(s.as_bytes());
+
+        self.origin = match cursor_position(reader, writer, CPR_TIMEOUT) {
+            Some([_, row]) => row,
+            None => {
+                winsize::from_fd(reader.as_raw_fd())
+                    .rows()
+                    .saturating_sub(h)
+                    + 1
+            }
+        };
+    }
+
+    /// prints the given lines above the viewport of an inline term
+    /// the lines are permanent; they scroll up with the primary screen and stay in the scrollback
+    /// the viewport moves down to make room for them then gets rendered again
+    pub fn insert_before(&mut self, writer: &mut impl Write, lines: &[&str]) {
+        let Viewport::Inline { h } = self.viewport else {
+            return;
+        };
+
+        let mut s = format!("\x1b[{};1f\x1b[J", self.origin);
+        lines.iter().for_each(|l| {
+            s.push_str(l);
+            s.push_str("\r\n");
+        });
+        self.reserve(&mut s);
+        _ = writer.write(s.as_bytes());
+
+        // the viewport goes down by as many rows as the lines took,
+        // at most until it reaches the bottom of the window, where the screen scrolls instead
+        let ws = winsize::from_ioctl();
+        let origin = self.origin + rows_of(lines, ws.cols());
+        self.origin = match ws.rows() {
+            // not a terminal, nothing to scroll
+            0 => origin,
+            rows => origin.min(rows.saturating_sub(h) + 1),
+        };
+
+        self.render(writer);
+    }
+
+    /// leaves the viewport of an inline term
+    /// the last rendered frame stays on the screen, and later in the scrollback,
+    /// and the cursor moves to the line right below it
+    pub fn close_inline(&self, writer: &mut impl Write) {
+        let Viewport::Inline { h } = self.viewport else {
+            return;
+        };
+
+        let s = format!("\x1b[{};1f\r\n", self.origin + h - 1);
+
+        _ = writer.write(s.as_bytes());
+        _ = writer.flush();
+    }
+}
+
+// the window rows the given lines take once printed, the ones wider than the window wrap
+// every grapheme is counted as one column
+fn rows_of(lines: &[&str], cols: u16) -> u16 {
+    lines
+        .iter()
+        .map(|l| match cols {
+            0 => 1,
+            cols => graphemes(l).count().max(1).div_ceil(cols as usize) as u16,
+        })
+        .sum()
+}
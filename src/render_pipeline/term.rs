@@ -1,24 +1,37 @@
 use std::collections::HashMap;
-use std::io::{StdoutLock, Write};
+use std::io::Write;
 use std::str::Chars;
 
 use crate::components::*;
+use crate::console::{title::set_title, Capabilities};
+use crate::grapheme::Grapheme;
 use crate::space::{border::Border, padding::Padding};
 use crate::themes::Style;
 
-use super::spread_padding;
+use super::{osc8, spread_padding, Cell};
 
 impl Term {
     /// renders the cursor in the self cx, cy position
-    pub fn render_cursor(&self, writer: &mut StdoutLock) {
-        let pos = format!("\x1b[{};{}f", self.cy, self.cx);
+    pub fn render_cursor(&self, writer: &mut impl Write) {
+        let pos = self.cursor_position();
         _ = writer.write(pos.as_bytes());
     }
 
+    // the sequence that moves the cursor to the self cx, cy position
+    // inline terms' positions are relative to the viewport origin
+    fn cursor_position(&self) -> String {
+        match self.viewport {
+            Viewport::Fullscreen => format!("\x1b[{};{}f", self.cy, self.cx),
+            Viewport::Inline { .. } => {
+                format!("\x1b[{};{}f", self.origin + self.cy.max(1) - 1, self.cx)
+            }
+        }
+    }
+
     /// renders only the text objects that have seen some value/border change since the last event
     /// loop iteration, either through user interaction or some background events being triggered
     // DEPRECATED
-    // pub fn live_render(&self, writer: &mut StdoutLock) {
+    // pub fn live_render(&self, writer: &mut impl Write) {
     //     self.changed().iter().for_each(|t| match t.change {
     //         2 => t.render_value(writer),
     //         4 => t.render_border(writer),
@@ -39,7 +52,7 @@ impl Term {
 
     pub fn property_render(
         &self,
-        writer: &mut StdoutLock,
+        writer: &mut impl Write,
         key: &str,
         br: Property,
         vr: Property,
@@ -82,7 +95,7 @@ impl Term {
 
     // NOTE: this method renders the entire component; both border and value
     // since it has no way of telling which part to render
-    pub fn attribute_render(&self, writer: &mut StdoutLock, attr: &str) {
+    pub fn attribute_render(&self, writer: &mut impl Write, attr: &str) {
         self.containers.iter().for_each(|c| {
             if c.attributes.contains(attr) {
                 c.render(writer);
@@ -96,11 +109,11 @@ impl Term {
         });
     }
 
-    pub fn partial_render(&self, writer: &mut StdoutLock) {}
+    pub fn partial_render(&self, writer: &mut impl Write) {}
 
-    fn prepare(&self) -> (Vec<Option<char>>) {
-        let mut lines: Vec<Option<char>> = vec![];
-        lines.resize((self.w * self.h) as usize, None);
+    fn prepare(&self) -> (Vec<Cell<'_>>) {
+        let mut lines: Vec<Cell> = vec![];
+        lines.resize((self.w * self.h) as usize, (None, None));
 
         self.containers.iter().for_each(|c| {
             let mut idx = c.x0 + c.y0 * self.w;
@@ -111,7 +124,7 @@ impl Term {
                 // write the item line inside the container lines
                 for cidx in 0..cwx {
                     let cell = cells[(cidx + line * cwx) as usize];
-                    if cell.is_some() {
+                    if cell.0.is_some() {
                         lines[idx as usize] = cell;
                     }
                     idx += 1;
@@ -143,24 +156,46 @@ impl Term {
     // this is expected behavior, although it's bad
     // need a way to map whatever style to some range of positions in the term buffer
     // that way, atomic style implementation becomes easy to call from anywhere
-    pub fn render(&mut self, writer: &mut StdoutLock) {
+    /// the frame is wrapped in a synchronized update if the terminal supports it
+    /// hyperlinks are wrapped in OSC 8 sequences if the terminal is known to support them
+    pub fn render(&mut self, writer: &mut impl Write) {
         let cells = self.prepare();
 
+        let sync = Capabilities::probed(|c| c.synchronized_output);
+        let links = Capabilities::probed(|c| c.hyperlinks);
+
         let mut s = String::new();
+        if sync {
+            s.push_str("\x1b[?2026h");
+        }
+        if let Viewport::Inline { .. } = self.viewport {
+            // back to the viewport origin
+            s.push_str(&format!("\x1b[{};1f", self.origin));
+        }
 
         let mut line = 0;
         let mut idx = 0;
+        let mut open = None;
 
-        cells.iter().for_each(|c| {
+        cells.iter().for_each(|(c, link)| {
+            if links && *link != open {
+                s.push_str(&osc8(*link));
+                open = *link;
+            }
             if let Some(ch) = c {
                 // print!("found char, ");
-                s.push(*ch);
+                s.push_str(ch.as_str());
             } else {
                 // print!("found space, ");
                 s.push_str("\x1b[C");
             }
             idx += 1;
             if idx == self.w {
+                // links never stay open over the cursor move to the next line
+                if open.is_some() {
+                    s.push_str(&osc8(None));
+                    open = None;
+                }
                 idx = 0;
                 line += 1;
                 if line < self.h - 1 {
@@ -172,22 +207,39 @@ impl Term {
 
         assert_eq!(line, self.h);
 
-        let pos = format!("\x1b[{};{}f", self.cy, self.cx);
+        let pos = self.cursor_position();
         s.push_str(&pos);
+        if sync {
+            s.push_str("\x1b[?2026l");
+        }
         // println!("{}", s);
         _ = writer.write(s.as_bytes());
         _ = writer.flush();
     }
 
+    /// sets the window title to this term's title
+    /// does nothing if the term has no title
+    pub fn render_title(&self, writer: &mut impl Write) {
+        if let Some(title) = &self.title {
+            set_title(writer, title);
+        }
+    }
+
     /// clears the whole terminal display
     /// first implementation of clear
-    pub fn clear(&self, writer: &mut StdoutLock) {
-        writer.write(b"\x1b[H\x1b[J");
+    /// an inline term only clears its viewport lines
+    pub fn clear(&self, writer: &mut impl Write) {
+        match self.viewport {
+            Viewport::Fullscreen => writer.write(b"\x1b[H\x1b[J"),
+            Viewport::Inline { .. } => {
+                writer.write(format!("\x1b[{};1f\x1b[J", self.origin).as_bytes())
+            }
+        };
     }
 
     /// clears the whole terminal display
     /// second implementation of clear
-    pub fn clear1(&self, writer: &mut StdoutLock) {
+    pub fn clear1(&self, writer: &mut impl Write) {
         let mut s = String::from("\x1b[H");
         (0..self.h)
             .into_iter()
@@ -1,22 +1,24 @@
 use std::collections::HashMap;
-use std::io::{StdoutLock, Write};
+use std::io::Write;
 use std::str::Chars;
 
 use crate::components::*;
+use crate::console::Capabilities;
+use crate::grapheme::Grapheme;
 use crate::space::{border::Border, padding::Padding};
 use crate::themes::Style;
 
-use super::spread_padding;
+use super::{osc8, spread_padding, Cell};
 
 impl Text {
     /// wrapper around the render_border and render_value method calls
-    pub fn render(&self, writer: &mut StdoutLock) {
+    pub fn render(&self, writer: &mut impl Write) {
         self.render_border(writer);
         self.render_value(writer);
     }
 
     /// renders only the text border
-    pub fn render_border(&self, writer: &mut StdoutLock) {
+    pub fn render_border(&self, writer: &mut impl Write) {
         let [por, pol, pot, pob, pir, pil, pit, pib] = spread_padding(&self.padding);
         let [xb, yb] = [self.ax0 - pil - 1, self.ay0 - pit - 1];
         let mut s = format!("{}\x1b[{};{}f", &self.bstyle, yb, xb);
@@ -53,8 +55,10 @@ impl Text {
     }
 
     /// renders only the text value
-    pub fn render_value(&self, writer: &mut StdoutLock) {
+    /// hyperlinks are wrapped in OSC 8 sequences if the terminal is known to support them
+    pub fn render_value(&self, writer: &mut impl Write) {
         let h0 = self.ay0;
+        let links = !self.hyperlinks.is_empty() && Capabilities::probed(|c| c.hyperlinks);
 
         let del = |s: &mut String, y: u16| {
             *s += &format!("\x1b[{};{}f\x1b[{}X", y, self.ax0, self.w);
@@ -62,14 +66,28 @@ impl Text {
 
         let put = |s: &mut String, y: u16| {
             *s += &format!("\x1b[{};{}f", h0 + y, self.ax0);
+            let mut open: Option<&Hyperlink> = None;
             for idx in 0..self.w {
-                let c = self.value[(idx + y * self.w) as usize];
+                let cell = (idx + y * self.w) as usize;
+                if links {
+                    let link = self.hyperlink_at(cell);
+                    if link != open {
+                        s.push_str(&osc8(link));
+                        open = link;
+                    }
+                }
+
+                let c = self.value[cell];
                 if c.is_some() {
-                    s.push(c.unwrap());
+                    s.push_str(c.unwrap().as_str());
                 } else {
                     s.push_str("\x1b[C")
                 };
             }
+            // links never stay open over the cursor move to the next line
+            if open.is_some() {
+                s.push_str(&osc8(None));
+            }
         };
 
         let mut s = format!("{}", &self.vstyle);
@@ -125,23 +143,23 @@ impl Text {
     }
 
     // this should be used inside the container prepare method
-    pub(super) fn prepare(&self) -> (Vec<Option<char>>, [u16; 2]) {
+    pub(super) fn prepare(&self) -> (Vec<Cell<'_>>, [u16; 2]) {
         // make out each line of the item, padding and border included
         // then render line
         // until all lines are rendered
-        let mut lines: Vec<Option<char>> = vec![];
+        let mut lines: Vec<Cell> = vec![];
         // wx is the number of chars in a line
         // hx is the number of lines
         let [wx, mut hx] = self.decorate();
 
-        lines.resize((wx * hx) as usize, None);
+        lines.resize((wx * hx) as usize, (None, None));
 
         self.process(&mut lines);
 
         (lines, [wx, hx])
     }
 
-    fn process(&self, lines: &mut Vec<Option<char>>) {
+    fn process<'a>(&'a self, lines: &mut Vec<Cell<'a>>) {
         let [por, pol, pot, pob, pir, pil, pit, pib] = spread_padding(&self.padding);
 
         let [wx, hx] = self.decorate();
@@ -193,10 +211,10 @@ impl Text {
         }
     }
 
-    fn process_none(
-        &self,
+    fn process_none<'a>(
+        &'a self,
         c: char,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'a>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -210,10 +228,10 @@ impl Text {
     ) {
     }
 
-    fn process_uniform(
-        &self,
+    fn process_uniform<'a>(
+        &'a self,
         c: char,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'a>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -245,7 +263,7 @@ impl Text {
 
             // we fill value length + inner padding right + left with border value
             for i in 0..pil + 1 + self.w + pir + 1 {
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
             }
             // println!("lines ==> {:?}", lines);
@@ -263,12 +281,12 @@ impl Text {
                 // new line we skip padding outer left
                 idx += pol;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skip inner left and right padding and the value len
                 idx += pil + self.w + pir;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skip outer right padding
                 idx += por;
@@ -286,7 +304,7 @@ impl Text {
                 // new line we skip padding outer left
                 idx += pol;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skip inner left padding
                 idx += pil;
@@ -304,7 +322,7 @@ impl Text {
                     // );
                     let i = vi + (self.w * (line - pot - 1 - pit)) as usize;
                     if i < self.value.len() {
-                        lines[idx as usize] = self.value[i];
+                        lines[idx as usize] = (self.value[i], self.hyperlink_at(i));
                     }
                     idx += 1;
                     // log_buf(&lines, wx, hx);
@@ -312,7 +330,7 @@ impl Text {
                 // skip inner right padding
                 idx += pir;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skip outer right padding
                 idx += por;
@@ -328,12 +346,12 @@ impl Text {
                 // new line we skip padding outer left
                 idx += pol;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skip inner left and right padding and the value width
                 idx += pil + self.w + pir;
                 // border cell
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
                 // skipp outer right padding
                 idx += por;
@@ -346,7 +364,7 @@ impl Text {
             idx += pol;
             // we fill value length + inner padding right + left with border value
             for i in 0..pil + 1 + self.w + pir + 1 {
-                lines[idx as usize] = Some(c);
+                lines[idx as usize] = (Some(c.into()), None);
                 idx += 1;
             }
             // println!("{}: idx = {}, line = {}", line!(), idx, line,);
@@ -361,15 +379,15 @@ impl Text {
         }
     }
 
-    fn process_polyform(
-        &self,
+    fn process_polyform<'a>(
+        &'a self,
         trcorner: char,
         tlcorner: char,
         blcorner: char,
         brcorner: char,
         btb: char,
         blr: char,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'a>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -399,18 +417,18 @@ impl Text {
         // log_buf(&lines, wx, hx);
 
         // we write the top left corner
-        lines[idx as usize] = Some(tlcorner);
+        lines[idx as usize] = (Some(tlcorner.into()), None);
         idx += 1;
 
         // we fill value length + inner padding right + left with border top/bottom value,
         // excluding the top corners
         for i in 0..pil + self.w + pir {
-            lines[idx as usize] = Some(btb);
+            lines[idx as usize] = (Some(btb.into()), None);
             idx += 1;
         }
 
         // we write the top right corner
-        lines[idx as usize] = Some(trcorner);
+        lines[idx as usize] = (Some(trcorner.into()), None);
         idx += 1;
 
         // println!("lines ==> {:?}", lines);
@@ -428,12 +446,12 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell, write border left/right value
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip inner left and right padding and the value len
             idx += pil + self.w + pir;
             // border cell, write border left/right value
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip outer right padding
             idx += por;
@@ -451,7 +469,7 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip inner left padding
             idx += pil;
@@ -469,7 +487,7 @@ impl Text {
                 // );
                 let i = vi + (self.w * (line - pot - 1 - pit)) as usize;
                 if i < self.value.len() {
-                    lines[idx as usize] = self.value[i];
+                    lines[idx as usize] = (self.value[i], self.hyperlink_at(i));
                 }
                 idx += 1;
                 // log_buf(&lines, wx, hx);
@@ -477,7 +495,7 @@ impl Text {
             // skip inner right padding
             idx += pir;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip outer right padding
             idx += por;
@@ -493,12 +511,12 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skip inner left and right padding and the value width
             idx += pil + self.w + pir;
             // border cell
-            lines[idx as usize] = Some(blr);
+            lines[idx as usize] = (Some(blr.into()), None);
             idx += 1;
             // skipp outer right padding
             idx += por;
@@ -510,15 +528,15 @@ impl Text {
         // we skip the outer left padding values
         idx += pol;
         // we write the border bottom left corner value
-        lines[idx as usize] = Some(blcorner);
+        lines[idx as usize] = (Some(blcorner.into()), None);
         idx += 1;
         // we fill value length + inner padding right + left with border value
         for i in 0..pil + self.w + pir {
-            lines[idx as usize] = Some(btb);
+            lines[idx as usize] = (Some(btb.into()), None);
             idx += 1;
         }
         // we write the border bottom right value
-        lines[idx as usize] = Some(brcorner);
+        lines[idx as usize] = (Some(brcorner.into()), None);
         idx += 1;
 
         // println!("{}: idx = {}, line = {}", line!(), idx, line,);
@@ -532,8 +550,8 @@ impl Text {
         // log_buf(&lines, wx, hx);
     }
 
-    fn process_manual(
-        &self,
+    fn process_manual<'a>(
+        &'a self,
         tlcorner: char,
         trcorner: char,
         blcorner: char,
@@ -550,7 +568,7 @@ impl Text {
         b0: &str,
         bp: char,
         b1: &str,
-        lines: &mut Vec<Option<char>>,
+        lines: &mut Vec<Cell<'a>>,
         wx: u16,
         hx: u16,
         por: u16,
@@ -619,19 +637,19 @@ impl Text {
         // log_buf(&lines, wx, hx);
 
         // we write the top left corner
-        lines[idx as usize] = Some(tlcorner);
+        lines[idx as usize] = (Some(tlcorner.into()), None);
         idx += 1;
 
         // log_buf(&lines, wx, hx);
 
         // write top border values
         while let Some(ch) = bt.next() {
-            lines[idx as usize] = Some(ch);
+            lines[idx as usize] = (Some(ch.into()), None);
             idx += 1;
         }
 
         // we write the top right corner
-        lines[idx as usize] = Some(trcorner);
+        lines[idx as usize] = (Some(trcorner.into()), None);
         idx += 1;
 
         // println!("lines ==> {:?}", lines);
@@ -649,12 +667,12 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell, write border left/right value
-            lines[idx as usize] = bl.next();
+            lines[idx as usize] = (bl.next().map(Grapheme::from), None);
             idx += 1;
             // skip inner left and right padding and the value len
             idx += pil + self.w + pir;
             // border cell, write border left/right value
-            lines[idx as usize] = br.next();
+            lines[idx as usize] = (br.next().map(Grapheme::from), None);
             idx += 1;
             // skip outer right padding
             idx += por;
@@ -672,7 +690,7 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = bl.next();
+            lines[idx as usize] = (bl.next().map(Grapheme::from), None);
             idx += 1;
             // skip inner left padding
             idx += pil;
@@ -690,7 +708,7 @@ impl Text {
                 // );
                 let i = vi + (self.w * (line - pot - 1 - pit)) as usize;
                 if i < self.value.len() {
-                    lines[idx as usize] = self.value[i];
+                    lines[idx as usize] = (self.value[i], self.hyperlink_at(i));
                 }
                 idx += 1;
                 // log_buf(&lines, wx, hx);
@@ -698,7 +716,7 @@ impl Text {
             // skip inner right padding
             idx += pir;
             // border cell
-            lines[idx as usize] = br.next();
+            lines[idx as usize] = (br.next().map(Grapheme::from), None);
             idx += 1;
             // skip outer right padding
             idx += por;
@@ -714,12 +732,12 @@ impl Text {
             // new line we skip padding outer left
             idx += pol;
             // border cell
-            lines[idx as usize] = bl.next();
+            lines[idx as usize] = (bl.next().map(Grapheme::from), None);
             idx += 1;
             // skip inner left and right padding and the value width
             idx += pil + self.w + pir;
             // border cell
-            lines[idx as usize] = br.next();
+            lines[idx as usize] = (br.next().map(Grapheme::from), None);
             idx += 1;
             // skipp outer right padding
             idx += por;
@@ -732,17 +750,17 @@ impl Text {
         idx += pol;
 
         // we write the border bottom left corner value
-        lines[idx as usize] = Some(blcorner);
+        lines[idx as usize] = (Some(blcorner.into()), None);
         idx += 1;
 
         // write bottom border values
         while let Some(ch) = bb.next() {
-            lines[idx as usize] = Some(ch);
+            lines[idx as usize] = (Some(ch.into()), None);
             idx += 1;
         }
 
         // we write the border bottom right value
-        lines[idx as usize] = Some(brcorner);
+        lines[idx as usize] = (Some(brcorner.into()), None);
         idx += 1;
 
         // println!("{}: idx = {}, line = {}", line!(), idx, line,);